@@ -2,34 +2,323 @@
 //
 // SPDX-License-Identifier: GPL-3.0-only
 
+#![cfg_attr(not(any(feature = "std", test)), no_std)]
+
 #[cfg(test)]
 mod tests;
 
-use std::{borrow::Cow, fmt::Display};
+extern crate alloc;
 
-trait SplitTwice<'a> {
-    fn split_twice(self, delim: &'a str) -> Option<(&'a str, &'a str, &'a str)>;
-}
+use alloc::{
+    borrow::Cow,
+    boxed::Box,
+    collections::BTreeMap,
+    format,
+    string::{String, ToString},
+    vec,
+    vec::Vec,
+};
+use core::{fmt::Display, time::Duration};
 
-impl<'a> SplitTwice<'a> for &'a str {
-    fn split_twice(self: &'a str, delim: &'a str) -> Option<(&'a str, &'a str, &'a str)> {
-        self.split_once(delim)
-            .and_then(|(a, b)| b.split_once(delim).map(|(b, c)| (a, b, c)))
+// Float rounding lives in std, so without it fall back to libm
+mod float {
+    #[cfg(feature = "std")]
+    pub fn round(x: f32) -> f32 {
+        x.round()
+    }
+    #[cfg(not(feature = "std"))]
+    pub fn round(x: f32) -> f32 {
+        libm::roundf(x)
+    }
+    #[cfg(feature = "std")]
+    pub fn ceil(x: f32) -> f32 {
+        x.ceil()
+    }
+    #[cfg(not(feature = "std"))]
+    pub fn ceil(x: f32) -> f32 {
+        libm::ceilf(x)
+    }
+    #[cfg(feature = "std")]
+    pub fn div_euclid(x: f32, y: f32) -> f32 {
+        x.div_euclid(y)
+    }
+    #[cfg(not(feature = "std"))]
+    pub fn div_euclid(x: f32, y: f32) -> f32 {
+        let quotient = libm::truncf(x / y);
+        if x % y < 0.0 {
+            if y > 0.0 {
+                quotient - 1.0
+            } else {
+                quotient + 1.0
+            }
+        } else {
+            quotient
+        }
+    }
+    #[cfg(feature = "std")]
+    pub fn rem_euclid(x: f32, y: f32) -> f32 {
+        x.rem_euclid(y)
+    }
+    #[cfg(not(feature = "std"))]
+    pub fn rem_euclid(x: f32, y: f32) -> f32 {
+        let remainder = x % y;
+        if remainder < 0.0 {
+            remainder + y.abs()
+        } else {
+            remainder
+        }
     }
 }
 
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Recipe<'a> {
     pub preface: Cow<'a, str>,
+    pub front_matter: Option<BTreeMap<String, String>>,
+    pub title: Option<Cow<'a, str>>,
+    pub yield_: Option<Yield<'a>>,
+    pub difficulty: Option<Difficulty>,
+    /// The rating as a fraction of full marks, so 'Rating: 4/5' is 0.8.
+    pub rating: Option<f32>,
+    // Whether an ingredients header was found at all, as a recipe without
+    // one has no ingredients either but is missing more than an empty list
+    pub has_ingredients_section: bool,
     pub ingredients: Vec<Ingredient<'a>>,
+    pub sections: Vec<IngredientSection<'a>>,
     pub instructions: Cow<'a, str>,
+    // A trailing '## Notes' section, starting with its heading
+    pub notes: Option<Cow<'a, str>>,
 }
 
 impl<'a> Recipe<'a> {
+    pub fn all_ingredients(&self) -> impl Iterator<Item = &Ingredient<'a>> {
+        let sections = self.sections.iter().flat_map(|s| &s.ingredients);
+        self.ingredients.iter().chain(sections)
+    }
+    pub fn ingredient_count(&self) -> usize {
+        self.all_ingredients().count()
+    }
+    /// Whether the recipe has no ingredients and no instructions, whether
+    /// or not it has an ingredients header.
+    pub fn is_empty(&self) -> bool {
+        self.ingredient_count() == 0 && self.instructions.trim().is_empty()
+    }
+    pub fn quantity_summary(&self) -> QuantitySummary {
+        let mut summary = QuantitySummary::default();
+        for ingredient in self.all_ingredients() {
+            let count = match ingredient.quantity {
+                Quantity::None => &mut summary.none,
+                Quantity::ToTaste { .. } => &mut summary.to_taste,
+                Quantity::Simple(_) => &mut summary.simple,
+                Quantity::Fixed(_) => &mut summary.fixed,
+                Quantity::Volume(_) => &mut summary.volume,
+                Quantity::Weight(_) => &mut summary.weight,
+                Quantity::Range { .. } => &mut summary.range,
+                Quantity::Package { .. } => &mut summary.package,
+            };
+            *count += 1;
+        }
+        summary
+    }
+    /// All of the ingredients measured by volume added together, such as
+    /// for picking a big enough bowl. Other quantities are left out.
+    pub fn total_volume(&self) -> Volume {
+        self.all_ingredients()
+            .filter_map(|ingredient| match &ingredient.quantity {
+                Quantity::Volume(volume) => Some(volume.clone()),
+                _ => None,
+            })
+            .sum()
+    }
+    /// Looks for ingredients that were probably written wrong. Lints
+    /// refer to ingredients by their position in `all_ingredients`.
+    pub fn validate(&self) -> Vec<Lint> {
+        let mut lints = vec![];
+        let mut names: Vec<String> = vec![];
+        for (index, ingredient) in self.all_ingredients().enumerate() {
+            let name = ingredient.name.trim().to_lowercase();
+            match &ingredient.quantity {
+                // A number or unit in the name means the amount didn't parse
+                Quantity::None => {
+                    let first_word = name.split_whitespace().next().unwrap_or_default();
+                    if name.contains(|c: char| c.is_ascii_digit())
+                        || Volume::parse("1", first_word).is_some()
+                        || Weight::parse("1", first_word).is_some()
+                    {
+                        lints.push(Lint {
+                            index,
+                            kind: LintKind::MissingQuantity,
+                        });
+                    }
+                }
+                Quantity::Volume(volume)
+                    if volume.quarter_teaspoons > quarter_teaspoons::IMPLAUSIBLE =>
+                {
+                    lints.push(Lint {
+                        index,
+                        kind: LintKind::ImplausibleVolume,
+                    });
+                }
+                _ => (),
+            }
+            if let Some(first) = names.iter().position(|n| *n == name) {
+                lints.push(Lint {
+                    index,
+                    kind: LintKind::DuplicateIngredient { first },
+                });
+            }
+            names.push(name);
+        }
+        lints
+    }
+    /// Compares ingredients by name with another version of the recipe.
+    pub fn diff<'r>(&'r self, other: &'r Recipe) -> RecipeDiff<'r> {
+        let key = |i: &Ingredient| i.name.trim().to_lowercase();
+        let theirs: Vec<_> = other.all_ingredients().collect();
+        let mut matched = vec![false; theirs.len()];
+        let mut diff = RecipeDiff::default();
+        for ingredient in self.all_ingredients() {
+            let name = key(ingredient);
+            let found = (0..theirs.len()).find(|&i| !matched[i] && key(theirs[i]) == name);
+            let Some(index) = found else {
+                diff.removed.push(ingredient.name.trim());
+                continue;
+            };
+            matched[index] = true;
+            if ingredient.quantity != theirs[index].quantity {
+                diff.changed.push(IngredientChange {
+                    name: theirs[index].name.trim(),
+                    before: &ingredient.quantity,
+                    after: &theirs[index].quantity,
+                });
+            }
+        }
+        diff.added = theirs
+            .iter()
+            .zip(matched)
+            .filter(|(_, matched)| !matched)
+            .map(|(ingredient, _)| ingredient.name.trim())
+            .collect();
+        diff
+    }
+    /// Reads a 'Prep: 20 min' or 'Prep time: 1 hr 15 min' line from the
+    /// preface.
+    pub fn prep_time(&self) -> Option<Duration> {
+        labeled_value(&self.preface, &PREP_LABELS, parse_duration)
+    }
+    /// Reads a 'Cook: 30 min' or 'Cook time: 1 hr' line from the preface.
+    pub fn cook_time(&self) -> Option<Duration> {
+        labeled_value(&self.preface, &COOK_LABELS, parse_duration)
+    }
+    pub fn total_time(&self) -> Option<Duration> {
+        match (self.prep_time(), self.cook_time()) {
+            (None, None) => None,
+            (prep, cook) => Some(
+                prep.unwrap_or_default()
+                    .saturating_add(cook.unwrap_or_default()),
+            ),
+        }
+    }
+    /// The prose of the preface, without its front matter, title, yield,
+    /// times, difficulty, rating or the heading the ingredients start under.
+    pub fn preface_without_metadata(&self) -> String {
+        let (_, body) = split_front_matter(&self.preface);
+        let mut lines: Vec<&str> = body.lines().collect();
+        // The ingredients heading ends the preface
+        while lines.last().is_some_and(|line| line.trim().is_empty()) {
+            lines.pop();
+        }
+        if lines.last().is_some_and(|line| line.starts_with('#')) {
+            lines.pop();
+        }
+        let title = lines.iter().position(|line| !line.trim().is_empty());
+        let mut out = String::new();
+        for (index, line) in lines.into_iter().enumerate() {
+            let is_metadata = (Some(index) == title && parse_title(line).is_some())
+                || Yield::parse(line).is_some()
+                || labeled_value(line, &PREP_LABELS, parse_duration).is_some()
+                || labeled_value(line, &COOK_LABELS, parse_duration).is_some()
+                || labeled_value(line, &["difficulty"], Difficulty::parse).is_some()
+                || labeled_value(line, &["rating"], parse_rating).is_some();
+            // Don't leave a gap of blank lines where the metadata was
+            if is_metadata || (line.trim().is_empty() && out.ends_with("\n\n")) {
+                continue;
+            }
+            out.push_str(line);
+            out.push('\n');
+        }
+        out.trim().to_string()
+    }
+    pub fn temperatures(&self) -> Vec<Temperature> {
+        Temperature::find_all(&self.instructions)
+    }
+    pub fn ingredient_names(&self) -> impl Iterator<Item = &str> {
+        self.all_ingredients().map(|i| i.name.trim())
+    }
+    pub fn steps(&self) -> Vec<&str> {
+        self.marked_steps()
+            .into_iter()
+            .map(|(step, _)| step)
+            .collect()
+    }
+    /// The steps written as a task list, like `- [x] Preheat the oven`,
+    /// along with whether each has been checked off.
+    pub fn checklist(&self) -> Vec<TaskStep<'_>> {
+        self.marked_steps()
+            .into_iter()
+            .filter_map(|(text, checked)| {
+                Some(TaskStep {
+                    text,
+                    checked: checked?,
+                })
+            })
+            .collect()
+    }
+    // Split the instructions into steps, noting whether task list items
+    // were checked
+    fn marked_steps(&self) -> Vec<(&str, Option<bool>)> {
+        let src: &str = &self.instructions;
+        // Use numbered or task list items as steps if there are any, and
+        // paragraphs if not
+        let numbered = src.lines().any(|line| step_marker(line).is_some());
+        let mut steps = vec![];
+        let mut step: Option<(usize, usize, Option<bool>)> = None;
+        let mut step_indent = 0;
+        let mut offset = 0;
+        for line in src.split_inclusive('\n') {
+            let start = offset;
+            offset += line.len();
+            let line = line.trim_end();
+            let end = start + line.len();
+            let indent = line.len() - line.trim_start().len();
+            if line.trim_start().starts_with('#') || (!numbered && line.is_empty()) {
+                steps.extend(step.take());
+            } else if numbered && let Some((len, checked)) = step_marker(line) {
+                steps.extend(step.take());
+                step = Some((start + len, end, checked));
+                step_indent = indent;
+            } else if numbered && leading_bullet(line).is_some() && indent <= step_indent {
+                // Other list items end the step, though nested ones are
+                // part of it
+                steps.extend(step.take());
+            } else if let Some((_, step_end, _)) = &mut step {
+                if !line.is_empty() {
+                    *step_end = end;
+                }
+            } else if !numbered && !line.is_empty() {
+                step = Some((start, end, None));
+            }
+        }
+        steps.extend(step);
+        steps
+            .into_iter()
+            .map(|(start, end, checked)| (src[start..end].trim(), checked))
+            .filter(|(step, _)| !step.is_empty())
+            .collect()
+    }
     pub fn divisors(&self) -> Vec<i32> {
         let quantities: Vec<f32> = self
-            .ingredients
-            .iter()
+            .all_ingredients()
             .filter_map(|i| match &i.quantity {
                 Quantity::Volume(volume) => Some(volume.quarter_teaspoons()),
                 _ => None,
@@ -37,179 +326,928 @@ impl<'a> Recipe<'a> {
             .collect();
         let max = quantities
             .iter()
-            .map(|i| i.ceil() as i32)
+            .map(|i| float::ceil(*i) as i32)
             .max()
             .unwrap_or(1);
         (1..=max)
-            .filter(|d| quantities.iter().all(|q| q.rem_euclid(*d as f32) == 0.0))
+            .filter(|d| {
+                quantities
+                    .iter()
+                    .all(|q| float::rem_euclid(*q, *d as f32) == 0.0)
+            })
             .collect()
     }
+    pub fn display_in(&self, system: UnitSystem) -> RecipeDisplay<'_, 'a> {
+        RecipeDisplay {
+            recipe: self,
+            system: Some(system),
+        }
+    }
+    /// Writes the recipe as tidy markdown, with `- ` bullets, two space
+    /// nesting and single spaces, rather than reproducing the source.
+    pub fn to_normalized_string(&self) -> String {
+        let mut out = self.preface.trim_end().to_string();
+        out.push_str("\n\n");
+        let push_ingredients = |out: &mut String, ingredients: &[Ingredient]| {
+            for (ingredient, depth) in ingredients.iter().zip(nesting_depths(ingredients)) {
+                out.push_str(&ingredient.to_normalized_string(depth));
+                out.push('\n');
+            }
+        };
+        push_ingredients(&mut out, &self.ingredients);
+        for section in &self.sections {
+            out.push('\n');
+            out.push_str(section.heading.trim());
+            out.push_str("\n\n");
+            push_ingredients(&mut out, &section.ingredients);
+        }
+        let instructions = self.instructions.trim();
+        if !instructions.is_empty() {
+            out.push('\n');
+            out.push_str(instructions);
+            out.push('\n');
+        }
+        if let Some(notes) = &self.notes {
+            out.push('\n');
+            out.push_str(notes.trim());
+            out.push('\n');
+        }
+        out
+    }
+    /// The same as [`Recipe::into_static`].
+    ///
+    /// ```
+    /// use maddi_recipe::Recipe;
+    ///
+    /// let src = String::from("# Toast\n\n## Ingredients\n\n- 1 slice of bread\n");
+    /// let recipe = Recipe::parse(&src);
+    /// let copy = recipe.to_owned_recipe();
+    /// let owned = recipe.into_owned();
+    /// drop(src);
+    /// assert_eq!(owned, copy);
+    /// assert_eq!(owned.ingredient_count(), 1);
+    /// ```
+    pub fn into_owned(self) -> Recipe<'static> {
+        self.into_static()
+    }
+    /// Copies the recipe into one that doesn't borrow from its source.
+    pub fn to_owned_recipe(&self) -> Recipe<'static> {
+        self.clone().into_static()
+    }
     pub fn into_static(self) -> Recipe<'static> {
         let Self {
             preface,
+            front_matter,
+            title,
+            yield_,
+            difficulty,
+            rating,
+            has_ingredients_section,
             ingredients,
+            sections,
             instructions,
+            notes,
         } = self;
         Recipe {
             preface: preface.to_string().into(),
+            front_matter,
+            title: title.map(|t| t.to_string().into()),
+            yield_: yield_.map(|y| y.into_static()),
+            difficulty,
+            rating,
+            has_ingredients_section,
             ingredients: ingredients.into_iter().map(|i| i.into_static()).collect(),
+            sections: sections.into_iter().map(|s| s.into_static()).collect(),
             instructions: instructions.to_string().into(),
+            notes: notes.map(|n| n.to_string().into()),
         }
     }
 }
 
+/// Parses an owned recipe, for when it can't borrow from its source.
+///
+/// ```
+/// use maddi_recipe::Recipe;
+///
+/// let src = String::from("# Toast\n\n## Ingredients\n\n- 1 slice of bread\n");
+/// let recipe = src.parse::<Recipe>().unwrap();
+/// drop(src);
+/// assert_eq!(recipe.title.as_deref(), Some("Toast"));
+/// assert_eq!(recipe.ingredient_count(), 1);
+/// ```
+impl core::str::FromStr for Recipe<'static> {
+    type Err = ParseError;
+    fn from_str(src: &str) -> Result<Self, Self::Err> {
+        Ok(Recipe::try_parse(src)?.into_static())
+    }
+}
+
 impl Display for Recipe<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}", self.preface)?;
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        RecipeDisplay {
+            recipe: self,
+            system: None,
+        }
+        .fmt(f)
+    }
+}
+
+/// Iterates over the ingredients before any titled sections, see
+/// [`Recipe::all_ingredients`] for every ingredient.
+impl<'r, 'a> IntoIterator for &'r Recipe<'a> {
+    type Item = &'r Ingredient<'a>;
+    type IntoIter = core::slice::Iter<'r, Ingredient<'a>>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.ingredients.iter()
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum UnitSystem {
+    /// Cups and spoons, as written in most recipes.
+    #[default]
+    Imperial,
+    /// Milliliters and liters.
+    Metric,
+}
+
+/// Displays a recipe with its volumes in the chosen [`UnitSystem`].
+#[derive(Debug, Clone, Copy)]
+pub struct RecipeDisplay<'r, 'a> {
+    recipe: &'r Recipe<'a>,
+    // Volumes are shown as they were written without a system
+    system: Option<UnitSystem>,
+}
+
+impl Display for RecipeDisplay<'_, '_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let recipe = self.recipe;
+        write!(f, "{}", recipe.preface)?;
+        for ingredient in &recipe.ingredients {
+            ingredient.write_in(f, self.system)?;
+        }
+        for section in &recipe.sections {
+            section.write_in(f, self.system)?;
+        }
+        write!(f, "{}", recipe.instructions)?;
+        if let Some(notes) = &recipe.notes {
+            write!(f, "{notes}")?;
+        }
+        Ok(())
+    }
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct QuantitySummary {
+    pub none: usize,
+    pub to_taste: usize,
+    pub simple: usize,
+    pub fixed: usize,
+    pub volume: usize,
+    pub weight: usize,
+    pub range: usize,
+    pub package: usize,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Lint {
+    pub index: usize,
+    pub kind: LintKind,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LintKind {
+    MissingQuantity,
+    ImplausibleVolume,
+    DuplicateIngredient { first: usize },
+}
+
+impl Display for Lint {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        let index = self.index;
+        match self.kind {
+            LintKind::MissingQuantity => {
+                write!(f, "ingredient {index} looks like it's missing a quantity")
+            }
+            LintKind::ImplausibleVolume => {
+                write!(f, "ingredient {index} has an implausibly large volume")
+            }
+            LintKind::DuplicateIngredient { first } => {
+                write!(f, "ingredient {index} is a duplicate of ingredient {first}")
+            }
+        }
+    }
+}
+
+/// The ingredients that differ between two versions of a recipe.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct RecipeDiff<'r> {
+    pub added: Vec<&'r str>,
+    pub removed: Vec<&'r str>,
+    pub changed: Vec<IngredientChange<'r>>,
+}
+
+impl RecipeDiff<'_> {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct IngredientChange<'r> {
+    pub name: &'r str,
+    pub before: &'r Quantity,
+    pub after: &'r Quantity,
+}
+
+/// A step written as a task list item, see [`Recipe::checklist`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TaskStep<'r> {
+    pub text: &'r str,
+    pub checked: bool,
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Yield<'a> {
+    pub count: u32,
+    pub unit: Option<Cow<'a, str>>,
+}
+
+impl<'a> Yield<'a> {
+    fn into_static(self) -> Yield<'static> {
+        Yield {
+            count: self.count,
+            unit: self.unit.map(|u| u.to_string().into()),
+        }
+    }
+    // Find the first 'Serves 4' or 'Yield: 12 muffins' line in a preface
+    fn parse(preface: &'a str) -> Option<Self> {
+        preface.lines().find_map(|line| {
+            let line = line.trim();
+            let tail = ["serves", "servings", "yield", "makes"]
+                .iter()
+                .find_map(|label| {
+                    let head = line.get(..label.len())?;
+                    head.eq_ignore_ascii_case(label)
+                        .then(|| &line[label.len()..])
+                })?;
+            let tail = tail.strip_prefix(':').unwrap_or(tail).trim_start();
+            let (count, unit) = tail.split_once(' ').unwrap_or((tail, ""));
+            let unit = unit.trim();
+            Some(Yield {
+                count: count.parse().ok()?,
+                unit: (!unit.is_empty()).then_some(unit.into()),
+            })
+        })
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum Difficulty {
+    Easy,
+    Medium,
+    Hard,
+}
+
+impl Difficulty {
+    fn parse(src: &str) -> Option<Self> {
+        match src.trim().to_lowercase().as_str() {
+            "easy" | "simple" => Some(Difficulty::Easy),
+            "medium" | "moderate" | "intermediate" => Some(Difficulty::Medium),
+            "hard" | "difficult" | "advanced" => Some(Difficulty::Hard),
+            _ => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct IngredientSection<'a> {
+    pub heading: Cow<'a, str>,
+    pub title: Cow<'a, str>,
+    pub ingredients: Vec<Ingredient<'a>>,
+}
+
+impl IngredientSection<'_> {
+    fn into_static(self) -> IngredientSection<'static> {
+        let Self {
+            heading,
+            title,
+            ingredients,
+        } = self;
+        IngredientSection {
+            heading: heading.to_string().into(),
+            title: title.to_string().into(),
+            ingredients: ingredients.into_iter().map(|i| i.into_static()).collect(),
+        }
+    }
+    fn write_in(
+        &self,
+        f: &mut impl core::fmt::Write,
+        system: Option<UnitSystem>,
+    ) -> core::fmt::Result {
+        write!(f, "{}", self.heading)?;
         for ingredient in &self.ingredients {
-            write!(f, "{ingredient}")?;
+            ingredient.write_in(f, system)?;
+        }
+        Ok(())
+    }
+    fn scale(&self, factor: f32) -> Self {
+        Self {
+            heading: self.heading.clone(),
+            title: self.title.clone(),
+            ingredients: self.ingredients.iter().map(|i| i.scale(factor)).collect(),
         }
-        write!(f, "{}", self.instructions)
     }
 }
 
-#[derive(Debug, Clone)]
+impl Display for IngredientSection<'_> {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.write_in(f, None)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Ingredient<'a> {
     pub indent: Cow<'a, str>,
+    pub bullet: char,
+    // The spaces after the bullet, when it's anything but a single space
+    pub bullet_spacing: Option<Cow<'a, str>>,
     pub quantity: Quantity,
+    // How a simple amount was written, like '1/2' or '½', so it can be
+    // written back the same way while it still reads as the quantity
+    pub amount_text: Option<Cow<'a, str>>,
+    // The cell border between the amount and name of a table row
+    pub divider: Option<Cow<'a, str>>,
+    // The ': ' or ' - ' after the name of an ingredient written name
+    // first, like 'Flour: 2 cups'
+    pub name_separator: Option<Cow<'a, str>>,
     pub name: Cow<'a, str>,
+    // A trailing clause saying how to prepare the ingredient, like the
+    // 'diced' in '2 apples, diced'
+    pub preparation: Option<Cow<'a, str>>,
+    pub note: Option<Cow<'a, str>>,
+    pub optional: bool,
+    // How an optional ingredient was marked, either a leading
+    // 'optional: ' or a trailing ' (optional)'
+    pub optional_marker: Option<Cow<'a, str>>,
+    // Fixed ingredients are left alone when scaling
+    pub fixed: bool,
+    // The ' {fixed}' annotation as written, including the space before it
+    pub fixed_marker: Option<Cow<'a, str>>,
+    pub trailing: Cow<'a, str>,
 }
 
 impl Display for Ingredient<'_> {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        write!(f, "{}- ", self.indent)?;
-        match &self.quantity {
-            Quantity::Simple(q) => write!(f, "{q} ")?,
-            Quantity::Volume(v) => write!(f, "{v} ")?,
-            _ => (),
-        };
-        write!(f, "{}", self.name)?;
-        Ok(())
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.write_in(f, None)
     }
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(tag = "type", content = "value"))]
 pub enum Quantity {
     None,
     Simple(f32),
+    // A count that stays the same however much is made, like equipment
+    Fixed(f32),
     Volume(Volume),
+    Weight(Weight),
+    Range {
+        low: f32,
+        high: f32,
+        // The dash between the amounts with any spaces around it, like
+        // the '-' in '2-3' or the ' – ' in '2 – 3'
+        separator: String,
+        unit: Option<String>,
+    },
+    ToTaste {
+        comma: bool,
+    },
+    // A count of packages holding a volume or weight each, like the
+    // '3 (14 oz) cans' of tomatoes
+    Package {
+        count: f32,
+        size: Box<Quantity>,
+        unit: String,
+    },
+}
+
+/// Counts are equal when they round to the same hundredth, and volumes
+/// and weights compare as they do on their own, so equal quantities
+/// always hash alike.
+impl PartialEq for Quantity {
+    fn eq(&self, other: &Self) -> bool {
+        let same = |a: f32, b: f32| quantize_count(a) == quantize_count(b);
+        match (self, other) {
+            (Quantity::None, Quantity::None) => true,
+            (Quantity::Simple(a), Quantity::Simple(b)) => same(*a, *b),
+            (Quantity::Fixed(a), Quantity::Fixed(b)) => same(*a, *b),
+            (Quantity::Volume(a), Quantity::Volume(b)) => a == b,
+            (Quantity::Weight(a), Quantity::Weight(b)) => a == b,
+            (
+                Quantity::Range {
+                    low,
+                    high,
+                    separator,
+                    unit,
+                },
+                Quantity::Range {
+                    low: other_low,
+                    high: other_high,
+                    separator: other_separator,
+                    unit: other_unit,
+                },
+            ) => {
+                same(*low, *other_low)
+                    && same(*high, *other_high)
+                    && separator == other_separator
+                    && unit == other_unit
+            }
+            (Quantity::ToTaste { comma }, Quantity::ToTaste { comma: other_comma }) => {
+                comma == other_comma
+            }
+            (
+                Quantity::Package { count, size, unit },
+                Quantity::Package {
+                    count: other_count,
+                    size: other_size,
+                    unit: other_unit,
+                },
+            ) => same(*count, *other_count) && size == other_size && unit == other_unit,
+            _ => false,
+        }
+    }
+}
+
+impl Eq for Quantity {}
+
+impl core::hash::Hash for Quantity {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        core::mem::discriminant(self).hash(state);
+        match self {
+            Quantity::Simple(count) | Quantity::Fixed(count) => quantize_count(*count).hash(state),
+            Quantity::Volume(volume) => volume.hash(state),
+            Quantity::Weight(weight) => weight.hash(state),
+            Quantity::Range {
+                low,
+                high,
+                separator,
+                unit,
+            } => {
+                quantize_count(*low).hash(state);
+                quantize_count(*high).hash(state);
+                separator.hash(state);
+                unit.hash(state);
+            }
+            Quantity::ToTaste { comma } => comma.hash(state),
+            Quantity::Package { count, size, unit } => {
+                quantize_count(*count).hash(state);
+                size.hash(state);
+                unit.hash(state);
+            }
+            Quantity::None => {}
+        }
+    }
+}
+
+// Buckets a float by multiples of `step`, so floats can be compared and
+// hashed alike
+fn quantize(value: f32, step: f32) -> i64 {
+    float::round(value / step) as i64
+}
+
+// Counts are compared to the hundredth
+fn quantize_count(count: f32) -> i64 {
+    quantize(count, 0.01)
 }
 
 #[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Volume {
     quarter_teaspoons: f32,
+    // Whether the volume was written in milliliters or liters, so it's
+    // shown that way again
+    metric: bool,
 }
 
 impl Volume {
+    /// Negative amounts are clamped to zero, as there's no such thing as
+    /// a negative amount of an ingredient.
+    pub fn from_quarter_teaspoons(quarter_teaspoons: f32) -> Self {
+        Volume {
+            quarter_teaspoons: quarter_teaspoons.max(0.0),
+            metric: false,
+        }
+    }
+    pub fn from_teaspoons(teaspoons: f32) -> Self {
+        Self::from_quarter_teaspoons(teaspoons * quarter_teaspoons::TEASPOON)
+    }
+    pub fn from_cups(cups: f32) -> Self {
+        Self::from_quarter_teaspoons(cups * quarter_teaspoons::CUP)
+    }
+    /// Volumes from milliliters are shown in milliliters and liters.
+    pub fn from_milliliters(milliliters: f32) -> Self {
+        Volume {
+            metric: true,
+            ..Self::from_quarter_teaspoons(milliliters * quarter_teaspoons::MILLILITER)
+        }
+    }
     pub fn quarter_teaspoons(&self) -> f32 {
         self.quarter_teaspoons
     }
+    /// Negative and NaN factors are treated as zero.
     pub fn scale(&self, factor: f32) -> Self {
         Volume {
-            quarter_teaspoons: self.quarter_teaspoons * factor,
+            quarter_teaspoons: self.quarter_teaspoons * scale_factor(factor),
+            metric: self.metric,
         }
     }
-}
-
-impl Display for Volume {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        use quarter_teaspoons::*;
-        let mut qtr_tsps = self.quarter_teaspoons;
-        let mut out = String::new();
-        // Take out as many cups as you can.
-        let mut plural = false;
-        let cups = qtr_tsps.div_euclid(CUP);
-        qtr_tsps = qtr_tsps.rem_euclid(CUP);
-        if cups > 0.0 {
-            out.push_str(&cups.to_string());
-            out.push(' ');
+    /// Like [`Volume::scale`], but `None` for a NaN factor or if the
+    /// volume would overflow.
+    pub fn checked_scale(&self, factor: f32) -> Option<Self> {
+        let scaled = self.scale(factor);
+        (!factor.is_nan() && scaled.quarter_teaspoons.is_finite()).then_some(scaled)
+    }
+    /// Whether the volume is equal to nothing at all, such as after
+    /// scaling by zero.
+    pub fn is_zero(&self) -> bool {
+        self.quarter_teaspoons.abs() <= quarter_teaspoons::EPSILON
+    }
+    fn quantized(&self) -> i64 {
+        quantize(self.quarter_teaspoons, quarter_teaspoons::EPSILON)
+    }
+    /// Whether the volumes are within `tolerance_qtsp` quarter teaspoons
+    /// of each other, for comparing amounts that went through some math.
+    pub fn approx_eq(&self, other: &Volume, tolerance_qtsp: f32) -> bool {
+        (self.quarter_teaspoons - other.quarter_teaspoons).abs() <= tolerance_qtsp
+    }
+    /// Rounds to the nearest whole number of `step`, such as the
+    /// nearest quarter cup. Steps that aren't positive leave the volume
+    /// as it is.
+    pub fn round_to_nearest(&self, step: &Volume) -> Self {
+        if step.quarter_teaspoons.is_nan() || step.quarter_teaspoons <= 0.0 {
+            return self.clone();
         }
-        // Check if 3/4 cup remains
-        if qtr_tsps >= THREE_QUARTER_CUP {
-            if !out.is_empty() {
-                out.push_str("+ ");
-                plural = true;
-            }
-            out.push_str("3/4 ");
-            qtr_tsps -= THREE_QUARTER_CUP;
+        let steps = float::round(self.quarter_teaspoons / step.quarter_teaspoons);
+        Volume {
+            quarter_teaspoons: steps * step.quarter_teaspoons,
+            metric: self.metric,
         }
-        // Check if 2/3 Cup remains
-        if qtr_tsps >= TWO_THIRDS_CUP {
-            if !out.is_empty() {
-                out.push_str("+ ");
-                plural = true;
-            }
-            out.push_str("2/3 ");
-            qtr_tsps -= TWO_THIRDS_CUP;
+    }
+    pub fn to_weight(&self, density: Density) -> Weight {
+        let milliliters = self.quarter_teaspoons / quarter_teaspoons::MILLILITER;
+        Weight {
+            grams: milliliters * density.grams_per_milliliter,
+            imperial: false,
         }
-        // Check if 1/2 Cup remains
-        if qtr_tsps >= HALF_CUP {
-            if !out.is_empty() {
-                out.push_str("+ ");
-                plural = true;
+    }
+    pub fn to_decimal_cups(&self) -> f32 {
+        self.quarter_teaspoons / quarter_teaspoons::CUP
+    }
+    pub fn convert_to(&self, unit: &str) -> Option<f32> {
+        let (unit_quarter_teaspoons, _) = Self::unit_quarter_teaspoons(unit)?;
+        Some(self.quarter_teaspoons / unit_quarter_teaspoons)
+    }
+    pub fn to_decimal_string(&self, unit: &str) -> Option<String> {
+        self.in_unit(unit, 2)
+    }
+    /// Writes the volume in just the given unit, to a tenth, like
+    /// '3.2 tbsp', or `None` for units that aren't volumes.
+    pub fn display_in_unit(&self, unit: &str) -> Option<String> {
+        self.in_unit(unit, 1)
+    }
+    // Write the volume in the given unit, rounded to some decimal places
+    fn in_unit(&self, unit: &str, places: u32) -> Option<String> {
+        let scale = 10u32.pow(places) as f32;
+        let amount = float::round(self.convert_to(unit)? * scale) / scale;
+        Some(format!("{amount} {unit}"))
+    }
+    pub fn to_string_large_units(&self) -> String {
+        use quarter_teaspoons::*;
+        let mut qtr_tsps = self.quarter_teaspoons;
+        let mut parts = vec![];
+        // Take out as many gallons and then quarts as you can
+        for (size, singular, plural) in [(GALLON, "gallon", "gallons"), (QUART, "quart", "quarts")]
+        {
+            let count = float::div_euclid(qtr_tsps, size);
+            if count > 0.0 {
+                let unit = if count > 1.0 { plural } else { singular };
+                parts.push(format!("{count} {unit}"));
+                qtr_tsps = float::rem_euclid(qtr_tsps, size);
             }
-            out.push_str("1/2 ");
-            qtr_tsps -= HALF_CUP;
         }
-        // Check if 1/3 Cup remains
-        if qtr_tsps >= THIRD_CUP {
-            if !out.is_empty() {
-                out.push_str("+ ");
-                plural = true;
-            }
-            out.push_str("1/3 ");
-            qtr_tsps -= THIRD_CUP;
+        // Leave the rest to the usual cups and spoons
+        if parts.is_empty() || qtr_tsps >= PINCH_THRESHOLD {
+            parts.push(Volume::from_quarter_teaspoons(qtr_tsps).to_string());
         }
-        // Check if 1/4 Cup remains
-        if qtr_tsps >= QUARTER_CUP {
+        parts.join(" + ")
+    }
+    /// Like the usual display, but with fractions like '1/2' written as
+    /// '½'. Fractions without a unicode character, like '1/16', are
+    /// left as they are.
+    pub fn to_unicode_string(&self) -> String {
+        let mut out = String::new();
+        for word in self.to_string().split(' ') {
+            let glyph = word.split_once('/').and_then(|(numerator, denominator)| {
+                VULGAR_FRACTIONS
+                    .iter()
+                    .find(|(_, n, d)| numerator.parse() == Ok(*n) && denominator.parse() == Ok(*d))
+                    .map(|(glyph, _, _)| *glyph)
+            });
             if !out.is_empty() {
-                out.push_str("+ ");
-                plural = true;
+                out.push(' ');
+            }
+            match glyph {
+                Some(glyph) => out.push(glyph),
+                None => out.push_str(word),
             }
-            out.push_str("1/4 ");
-            qtr_tsps -= QUARTER_CUP;
         }
-        // Add 'cups' or 'cup'
-        if cups > 1.0 || plural {
-            out.push_str("cups");
-        } else if !out.is_empty() {
-            out.push_str("cup");
+        out
+    }
+    pub fn to_metric_string(&self) -> String {
+        use quarter_teaspoons::*;
+        let milliliters = float::round(self.quarter_teaspoons / MILLILITER);
+        if milliliters < 1000.0 {
+            format!("{milliliters} ml")
+        } else {
+            format!(
+                "{} l",
+                float::round(self.quarter_teaspoons / LITER * 10.0) / 10.0
+            )
         }
+    }
+}
 
-        // Adding tablespoons
-        let mut has_tablespoons = false;
-        let mut plural = false;
-        let tablespoons = qtr_tsps.div_euclid(TABLESPOON);
-        qtr_tsps = qtr_tsps.rem_euclid(TABLESPOON);
-        if tablespoons > 0.0 {
-            has_tablespoons = true;
-            if !out.is_empty() {
-                out.push_str("+ ");
-            }
-            out.push_str(&format!("{tablespoons} "));
-        }
-        // As two teaspoons is more than half a tablespoon, we only
-        // do this one if we have less than two teaspoons
-        if (HALF_TABLESPOON..2.0 * TEASPOON).contains(&qtr_tsps) {
-            if !out.is_empty() {
-                out.push_str("+ ");
-            }
-            plural = has_tablespoons;
-            has_tablespoons = true;
-            out.push_str("1/2 ");
-            qtr_tsps -= HALF_TABLESPOON;
+impl core::ops::Add for Volume {
+    type Output = Volume;
+    fn add(self, rhs: Self) -> Self::Output {
+        Volume {
+            quarter_teaspoons: self.quarter_teaspoons + rhs.quarter_teaspoons,
+            // Stay metric only if both volumes were
+            metric: self.metric && rhs.metric,
         }
-        if tablespoons > 1.0 || plural {
-            out.push_str("tbsps");
-        } else if has_tablespoons {
-            out.push_str("tbsp")
+    }
+}
+
+impl core::ops::AddAssign for Volume {
+    fn add_assign(&mut self, rhs: Self) {
+        self.quarter_teaspoons += rhs.quarter_teaspoons;
+        self.metric &= rhs.metric;
+    }
+}
+
+impl core::iter::Sum for Volume {
+    fn sum<I: Iterator<Item = Self>>(iter: I) -> Self {
+        iter.reduce(|a, b| a + b)
+            .unwrap_or_else(|| Volume::from_quarter_teaspoons(0.0))
+    }
+}
+
+impl<'a> core::iter::Sum<&'a Volume> for Volume {
+    fn sum<I: Iterator<Item = &'a Volume>>(iter: I) -> Self {
+        iter.cloned().sum()
+    }
+}
+
+/// Volumes are equal when they round to the same multiple of
+/// `quarter_teaspoons::EPSILON`, as scaling and unit conversion leave
+/// `f32` error behind. Use [`Volume::approx_eq`] for a plain tolerance.
+impl PartialEq for Volume {
+    fn eq(&self, other: &Self) -> bool {
+        self.quantized() == other.quantized()
+    }
+}
+
+/// Volumes that round to the same multiple of `quarter_teaspoons::EPSILON`
+/// are equal, to agree with `PartialEq`.
+impl PartialOrd for Volume {
+    fn partial_cmp(&self, other: &Self) -> Option<core::cmp::Ordering> {
+        if self == other {
+            return Some(core::cmp::Ordering::Equal);
         }
+        self.quarter_teaspoons.partial_cmp(&other.quarter_teaspoons)
+    }
+}
 
-        // Adding teaspoons
-        let mut has_teaspoons = false;
-        let mut plural = false;
-        let teaspoons = qtr_tsps.div_euclid(TEASPOON);
-        qtr_tsps = qtr_tsps.rem_euclid(TEASPOON);
+impl Eq for Volume {}
+
+/// Hashes the volume rounded to `quarter_teaspoons::EPSILON`, the same
+/// as it's compared, so equal volumes hash alike.
+impl core::hash::Hash for Volume {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        self.quantized().hash(state);
+    }
+}
+
+/// Shows the volume in the system it was written in, in cups and spoons
+/// or in milliliters and liters.
+impl Display for Volume {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self.metric {
+            true => write!(f, "{}", self.to_metric_string()),
+            false => write!(f, "{}", VolumeFormatter::default().format(self)),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct VolumeFormatter {
+    denominators: Vec<u32>,
+    prefer_thirds: bool,
+    tablespoons: bool,
+    labels: UnitLabels,
+}
+
+impl Default for VolumeFormatter {
+    fn default() -> Self {
+        VolumeFormatter {
+            denominators: vec![2, 3, 4],
+            prefer_thirds: true,
+            tablespoons: true,
+            labels: UnitLabels::default(),
+        }
+    }
+}
+
+/// The words a [`VolumeFormatter`] writes for its units, so volumes can
+/// be shown in other languages.
+#[derive(Debug, Clone)]
+pub struct UnitLabels {
+    cup: (String, String),
+    tablespoon: (String, String),
+    teaspoon: (String, String),
+    pinch: String,
+}
+
+impl Default for UnitLabels {
+    fn default() -> Self {
+        UnitLabels {
+            cup: ("cup".to_string(), "cups".to_string()),
+            tablespoon: ("tbsp".to_string(), "tbsps".to_string()),
+            teaspoon: ("tsp".to_string(), "tsps".to_string()),
+            pinch: "a pinch".to_string(),
+        }
+    }
+}
+
+impl UnitLabels {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn cup(mut self, singular: &str, plural: &str) -> Self {
+        self.cup = (singular.to_string(), plural.to_string());
+        self
+    }
+    pub fn tablespoon(mut self, singular: &str, plural: &str) -> Self {
+        self.tablespoon = (singular.to_string(), plural.to_string());
+        self
+    }
+    pub fn teaspoon(mut self, singular: &str, plural: &str) -> Self {
+        self.teaspoon = (singular.to_string(), plural.to_string());
+        self
+    }
+    /// Sets what's written for amounts too small to measure.
+    pub fn pinch(mut self, pinch: &str) -> Self {
+        self.pinch = pinch.to_string();
+        self
+    }
+}
+
+// Write a unit's singular or plural label, followed by a space
+fn push_label(out: &mut String, (singular, plural): &(String, String), is_plural: bool) {
+    out.push_str(if is_plural { plural } else { singular });
+    out.push(' ');
+}
+
+impl VolumeFormatter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Sets the denominators allowed in fractions of a cup.
+    pub fn denominators(mut self, denominators: impl IntoIterator<Item = u32>) -> Self {
+        self.denominators = denominators.into_iter().collect();
+        self
+    }
+    pub fn labels(mut self, labels: UnitLabels) -> Self {
+        self.labels = labels;
+        self
+    }
+    /// When not preferring thirds, thirds of a cup are only used for
+    /// amounts that are exactly a third, leaving the rest to quarters.
+    pub fn prefer_thirds(mut self, prefer_thirds: bool) -> Self {
+        self.prefer_thirds = prefer_thirds;
+        self
+    }
+    /// Without tablespoons, whatever's left after the cups is written in
+    /// teaspoons, so 3 tsp stays '3 tsp' rather than becoming '1 tbsp'.
+    pub fn tablespoons(mut self, tablespoons: bool) -> Self {
+        self.tablespoons = tablespoons;
+        self
+    }
+    fn cup_fractions(&self) -> Vec<(u32, u32)> {
+        let gcd = |mut a: u32, mut b: u32| {
+            while b != 0 {
+                (a, b) = (b, a % b);
+            }
+            a
+        };
+        let mut fractions: Vec<(u32, u32)> = self
+            .denominators
+            .iter()
+            .flat_map(|&d| (1..d).map(move |n| (n, d)))
+            .filter(|&(n, d)| gcd(n, d) == 1)
+            .collect();
+        fractions
+            .sort_by(|(a, b), (c, d)| (*c as f32 / *d as f32).total_cmp(&(*a as f32 / *b as f32)));
+        fractions.dedup();
+        fractions
+    }
+    pub fn format(&self, volume: &Volume) -> String {
+        use quarter_teaspoons::*;
+        let mut qtr_tsps = volume.quarter_teaspoons;
+        // Anything too small to measure is just a pinch
+        if qtr_tsps > 0.0 && qtr_tsps < PINCH_THRESHOLD {
+            return self.labels.pinch.clone();
+        }
+        // Round to the nearest sixteenth of a teaspoon up front, as
+        // that's the smallest measure most kitchens have, so no stray
+        // fraction is left over once the larger units are taken out
+        qtr_tsps = float::round(qtr_tsps / SIXTEENTH_TEASPOON) * SIXTEENTH_TEASPOON;
+        let mut out = String::new();
+        // Take out as many cups as you can.
+        let mut plural = false;
+        let cups = float::div_euclid(qtr_tsps, CUP);
+        qtr_tsps = float::rem_euclid(qtr_tsps, CUP);
+        if cups > 0.0 {
+            out.push_str(&cups.to_string());
+            out.push(' ');
+        }
+        // Check which fractions of a cup remain, biggest first
+        for (numerator, denominator) in self.cup_fractions() {
+            let fraction = numerator as f32 / denominator as f32 * CUP;
+            let is_third = denominator % 3 == 0;
+            if qtr_tsps < fraction
+                || (is_third && !self.prefer_thirds && (qtr_tsps - fraction).abs() > EPSILON)
+            {
+                continue;
+            }
+            if !out.is_empty() {
+                out.push_str("+ ");
+                plural = true;
+            }
+            out.push_str(&format!("{numerator}/{denominator} "));
+            qtr_tsps -= fraction;
+        }
+        // Add 'cups' or 'cup'
+        if cups > 1.0 || plural || !out.is_empty() {
+            push_label(&mut out, &self.labels.cup, cups > 1.0 || plural);
+        }
+
+        // Adding tablespoons, unless they're turned off
+        if self.tablespoons {
+            let mut has_tablespoons = false;
+            let mut plural = false;
+            let tablespoons = float::div_euclid(qtr_tsps, TABLESPOON);
+            qtr_tsps = float::rem_euclid(qtr_tsps, TABLESPOON);
+            if tablespoons > 0.0 {
+                has_tablespoons = true;
+                if !out.is_empty() {
+                    out.push_str("+ ");
+                }
+                out.push_str(&format!("{tablespoons} "));
+            }
+            // Only a remainder of exactly half a tablespoon is written as
+            // one, so '1/2 tbsp' and '1 1/2 tsp' agree, and anything
+            // near it is left to the teaspoons
+            if (qtr_tsps - HALF_TABLESPOON).abs() <= EPSILON {
+                if !out.is_empty() {
+                    out.push_str("+ ");
+                }
+                plural = has_tablespoons;
+                has_tablespoons = true;
+                out.push_str("1/2 ");
+                qtr_tsps -= HALF_TABLESPOON;
+            }
+            if tablespoons > 1.0 || plural || has_tablespoons {
+                push_label(
+                    &mut out,
+                    &self.labels.tablespoon,
+                    tablespoons > 1.0 || plural,
+                );
+            }
+        }
+
+        // Adding teaspoons
+        let mut has_teaspoons = false;
+        let mut plural = false;
+        let teaspoons = float::div_euclid(qtr_tsps, TEASPOON);
+        qtr_tsps = float::rem_euclid(qtr_tsps, TEASPOON);
         if teaspoons > 0.0 {
             has_teaspoons = true;
             if !out.is_empty() {
@@ -235,173 +1273,2275 @@ impl Display for Volume {
             out.push_str("1/4 ");
             qtr_tsps -= QUARTER_TEASPOON;
         }
-        if qtr_tsps > 0.0 {
+        if qtr_tsps >= EIGHTH_TEASPOON {
             plural = has_teaspoons;
             has_teaspoons = true;
             if !out.is_empty() {
                 out.push_str("+ ");
             }
-            let tsps = qtr_tsps / 4.0;
-            match tsps {
-                0.0625 => out.push_str("1/16 "),
-                0.125 => out.push_str("1/8 "),
-                tsps => out.push_str(&format!("{tsps} ")),
+            out.push_str("1/8 ");
+            qtr_tsps -= EIGHTH_TEASPOON;
+        }
+        if qtr_tsps >= SIXTEENTH_TEASPOON {
+            plural = has_teaspoons;
+            has_teaspoons = true;
+            if !out.is_empty() {
+                out.push_str("+ ");
             }
+            out.push_str("1/16 ");
         }
-        if teaspoons > 1.0 || plural {
-            out.push_str("tsps");
-        } else if has_teaspoons {
-            out.push_str("tsp")
+        if teaspoons > 1.0 || plural || has_teaspoons {
+            push_label(&mut out, &self.labels.teaspoon, teaspoons > 1.0 || plural);
         }
-        // TODO
-
-        // Adding teaspoons
-        // - Check if what's left is greater or equal to two teaspoons
-        // - Do the rest
-        write!(f, "{out}")
+        out.trim_end().to_string()
     }
 }
 
 mod quarter_teaspoons {
+    pub const GALLON: f32 = 4.0 * QUART;
+    pub const QUART: f32 = 2.0 * PINT;
+    pub const PINT: f32 = 2.0 * CUP;
     pub const CUP: f32 = 16.0 * 3.0 * 4.0;
-    pub const THREE_QUARTER_CUP: f32 = 3.0 / 4.0 * CUP;
-    pub const TWO_THIRDS_CUP: f32 = 2.0 / 3.0 * CUP;
-    pub const HALF_CUP: f32 = 0.5 * CUP;
-    pub const THIRD_CUP: f32 = 1.0 / 3.0 * CUP;
-    pub const QUARTER_CUP: f32 = 1.0 / 4.0 * CUP;
+    pub const FLUID_OUNCE: f32 = 2.0 * TABLESPOON;
     pub const TABLESPOON: f32 = 3.0 * 4.0;
     pub const HALF_TABLESPOON: f32 = 0.5 * TABLESPOON;
     pub const TEASPOON: f32 = 4.0;
     pub const HALF_TEASPOON: f32 = 2.0;
     pub const QUARTER_TEASPOON: f32 = 1.0;
+    pub const EIGHTH_TEASPOON: f32 = 0.5;
+    pub const SIXTEENTH_TEASPOON: f32 = 0.25;
+    pub const DASH: f32 = EIGHTH_TEASPOON;
+    pub const PINCH: f32 = SIXTEENTH_TEASPOON;
+    pub const SMIDGEN: f32 = 0.5 * PINCH;
+    // Volumes smaller than this would round away to nothing
+    pub const PINCH_THRESHOLD: f32 = 0.5 * SIXTEENTH_TEASPOON;
+    // The largest difference still considered the same volume
+    pub const EPSILON: f32 = 0.01;
+    // More than any single ingredient in a home recipe should need
+    pub const IMPLAUSIBLE: f32 = 4.0 * GALLON;
+    // US customary teaspoons are defined as 4.92892 ml, so metric
+    // volumes don't land on whole quarter teaspoons.
+    pub const MILLILITER: f32 = TEASPOON / 4.92892;
+    pub const CENTILITER: f32 = 10.0 * MILLILITER;
+    pub const DECILITER: f32 = 100.0 * MILLILITER;
+    pub const LITER: f32 = 1000.0 * MILLILITER;
+}
+
+/// The size of each unit in quarter teaspoons, to use with
+/// [`Volume::quarter_teaspoons`] and [`Volume::from_quarter_teaspoons`].
+///
+/// ```
+/// use maddi_recipe::{Volume, units};
+///
+/// let volume = Volume::from_cups(1.5);
+/// assert_eq!(volume.quarter_teaspoons() / units::CUP, 1.5);
+/// assert_eq!(units::CUP, 16.0 * units::TABLESPOON);
+/// ```
+pub mod units {
+    pub use super::quarter_teaspoons::{
+        CENTILITER, CUP, DASH, DECILITER, EIGHTH_TEASPOON, FLUID_OUNCE, GALLON, HALF_TABLESPOON,
+        HALF_TEASPOON, LITER, MILLILITER, PINCH, PINT, QUART, QUARTER_TEASPOON, SIXTEENTH_TEASPOON,
+        SMIDGEN, TABLESPOON, TEASPOON,
+    };
 }
 
 impl Volume {
     fn parse(amount: &str, unit: &str) -> Option<Self> {
-        let amount = parse_f32(amount).ok()?;
-        let unit_quarter_teaspoons: f32 = match unit.to_lowercase().as_str() {
-            "cups" | "cup" => 16.0 * 3.0 * 4.0,
-            "tablespoon" | "tablespoons" | "tb" | "tbs" | "tbsp" | "tbsps" => 3.0 * 4.0,
-            "teaspoon" | "teaspoons" | "tsp" | "tsps" => 4.0,
-            _ => return None,
+        let (unit_quarter_teaspoons, metric) = Self::unit_quarter_teaspoons(unit)?;
+        // Small measures are usually written as 'a pinch' or 'a dash'
+        let amount = match amount {
+            "a" | "an" if unit_quarter_teaspoons <= quarter_teaspoons::DASH => 1.0,
+            amount => parse_unit_amount(amount)?,
         };
         Some(Self {
             quarter_teaspoons: amount * unit_quarter_teaspoons,
+            metric,
         })
     }
+    // How many quarter teaspoons are in a unit, and whether it's metric
+    fn unit_quarter_teaspoons(unit: &str) -> Option<(f32, bool)> {
+        use quarter_teaspoons::*;
+        // Abbreviations are sometimes written with periods, like 'tsp.'
+        let unit_quarter_teaspoons = match unit.to_lowercase().replace('.', "").as_str() {
+            "gal" | "gallon" | "gallons" => (GALLON, false),
+            "qt" | "quart" | "quarts" => (QUART, false),
+            "pt" | "pint" | "pints" => (PINT, false),
+            "cups" | "cup" => (CUP, false),
+            "fl oz" | "floz" | "fluid ounce" | "fluid ounces" => (FLUID_OUNCE, false),
+            "tablespoon" | "tablespoons" | "tb" | "tbs" | "tbsp" | "tbsps" => (TABLESPOON, false),
+            "teaspoon" | "teaspoons" | "tsp" | "tsps" => (TEASPOON, false),
+            "ml" | "milliliter" | "milliliters" => (MILLILITER, true),
+            "cl" | "centiliter" | "centiliters" => (CENTILITER, true),
+            "dl" | "deciliter" | "deciliters" => (DECILITER, true),
+            "l" | "liter" | "liters" => (LITER, true),
+            "dash" | "dashes" => (DASH, false),
+            "pinch" | "pinches" => (PINCH, false),
+            "smidgen" | "smidgens" => (SMIDGEN, false),
+            _ => return None,
+        };
+        Some(unit_quarter_teaspoons)
+    }
 }
 
-impl<'a> Recipe<'a> {
+#[derive(Debug, Clone)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Weight {
+    grams: f32,
+    imperial: bool,
+}
+
+impl Weight {
+    pub fn grams(&self) -> f32 {
+        self.grams
+    }
+    pub fn to_volume(&self, density: Density) -> Volume {
+        let milliliters = self.grams / density.grams_per_milliliter;
+        // Stay in the system the weight was written in
+        Volume {
+            metric: !self.imperial,
+            ..Volume::from_milliliters(milliliters)
+        }
+    }
+    /// Negative and NaN factors are treated as zero.
     pub fn scale(&self, factor: f32) -> Self {
-        Recipe {
-            preface: self.preface.clone(),
-            ingredients: self.ingredients.iter().map(|i| i.scale(factor)).collect(),
-            instructions: self.instructions.clone(),
+        Weight {
+            grams: self.grams * scale_factor(factor),
+            imperial: self.imperial,
         }
     }
-    pub fn parse(src: &'a str) -> Self {
-        // Find where the ingredients start
-        const INGREDIENTS: &str = "\n## Ingredients\n\n";
-        let Some(mut ingredients_start) = src.find(INGREDIENTS) else {
-            return Recipe {
-                preface: Cow::Borrowed(src),
-                ingredients: vec![],
-                instructions: Cow::Borrowed(""),
-            };
-        };
-        ingredients_start += INGREDIENTS.len();
-        // Seperate the preface, ingredients, and instructions
-        let (preface, src) = src.split_at(ingredients_start);
-        let (ingredients, instructions) = match src.find("\n##") {
-            Some(ingredients_end) => src.split_at(ingredients_end),
-            None => (src, ""),
+    fn parse(amount: &str, unit: &str) -> Option<Self> {
+        let (unit_grams, imperial) = Self::unit_grams(unit)?;
+        let amount = parse_unit_amount(amount)?;
+        Some(Self {
+            grams: amount * unit_grams,
+            imperial,
+        })
+    }
+    // How many grams are in a unit, and whether it's an imperial unit
+    fn unit_grams(unit: &str) -> Option<(f32, bool)> {
+        use grams::*;
+        let unit_grams = match unit.to_lowercase().replace('.', "").as_str() {
+            "mg" | "milligram" | "milligrams" => (MILLIGRAM, false),
+            "g" | "gram" | "grams" => (GRAM, false),
+            "kg" | "kilogram" | "kilograms" => (KILOGRAM, false),
+            "oz" | "ounce" | "ounces" => (OUNCE, true),
+            "lb" | "lbs" | "pound" | "pounds" => (POUND, true),
+            _ => return None,
         };
-        // Parse the ingredients
-        let ingredients = Ingredients(ingredients).map(Ingredient::parse).collect();
+        Some(unit_grams)
+    }
+}
 
-        // Return the recipe
+/// Weights are equal when they round to the same multiple of
+/// `grams::EPSILON`, regardless of whether they were written in metric
+/// or imperial units.
+impl PartialEq for Weight {
+    fn eq(&self, other: &Self) -> bool {
+        quantize(self.grams, grams::EPSILON) == quantize(other.grams, grams::EPSILON)
+    }
+}
+
+impl Eq for Weight {}
+
+/// Hashes the weight rounded to `grams::EPSILON`, the same as it's
+/// compared, like `Volume`.
+impl core::hash::Hash for Weight {
+    fn hash<H: core::hash::Hasher>(&self, state: &mut H) {
+        quantize(self.grams, grams::EPSILON).hash(state);
+    }
+}
+
+impl Display for Weight {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use grams::*;
+        // Weights are shown in the same system they were written in,
+        // rounded to two decimal places to hide conversion error.
+        let round = |amount: f32| float::round(amount * 100.0) / 100.0;
+        if self.imperial {
+            if self.grams >= POUND {
+                write!(f, "{} lb", round(self.grams / POUND))
+            } else {
+                write!(f, "{} oz", round(self.grams / OUNCE))
+            }
+        } else if self.grams >= KILOGRAM {
+            write!(f, "{} kg", round(self.grams / KILOGRAM))
+        } else if self.grams >= GRAM {
+            write!(f, "{} g", round(self.grams))
+        } else {
+            write!(f, "{} mg", round(self.grams / MILLIGRAM))
+        }
+    }
+}
+
+mod grams {
+    pub const MILLIGRAM: f32 = 0.001;
+    pub const GRAM: f32 = 1.0;
+    pub const KILOGRAM: f32 = 1000.0;
+    pub const OUNCE: f32 = 28.349_523;
+    pub const POUND: f32 = 16.0 * OUNCE;
+    // The largest difference still considered the same weight
+    pub const EPSILON: f32 = 0.01;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Temperature {
+    pub degrees: f32,
+    pub unit: TemperatureUnit,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub enum TemperatureUnit {
+    Celsius,
+    Fahrenheit,
+}
+
+impl Temperature {
+    pub fn to_celsius(&self) -> f32 {
+        match self.unit {
+            TemperatureUnit::Celsius => self.degrees,
+            TemperatureUnit::Fahrenheit => (self.degrees - 32.0) * 5.0 / 9.0,
+        }
+    }
+    pub fn to_fahrenheit(&self) -> f32 {
+        match self.unit {
+            TemperatureUnit::Celsius => self.degrees * 9.0 / 5.0 + 32.0,
+            TemperatureUnit::Fahrenheit => self.degrees,
+        }
+    }
+    // Finds every temperature written like '450°F' or '220 °C'
+    fn find_all(src: &str) -> Vec<Self> {
+        let mut temperatures = vec![];
+        for (degree, _) in src.match_indices('°') {
+            let number = src[..degree].trim_end();
+            let digits = number
+                .trim_end_matches(|c: char| c.is_ascii_digit() || c == '.')
+                .len();
+            let Ok(degrees) = number[digits..].parse::<f32>() else {
+                continue;
+            };
+            let mut unit = src[degree + '°'.len_utf8()..].trim_start().chars();
+            let unit = match (unit.next(), unit.next()) {
+                (Some('C' | 'c'), next) if !next.is_some_and(char::is_alphabetic) => {
+                    TemperatureUnit::Celsius
+                }
+                (Some('F' | 'f'), next) if !next.is_some_and(char::is_alphabetic) => {
+                    TemperatureUnit::Fahrenheit
+                }
+                _ => continue,
+            };
+            temperatures.push(Temperature { degrees, unit });
+        }
+        temperatures
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SortKey {
+    /// Largest first, with volumes before weights, then ranges, then
+    /// counts, then ingredients to taste or without a quantity.
+    Amount,
+    /// Alphabetically, ignoring case.
+    Name,
+}
+
+fn sort_ingredients(ingredients: &mut Vec<Ingredient<'_>>, by: SortKey) {
+    // Anything before the first ingredient's own line, like a table's
+    // header, stays at the top
+    let Some(first) = ingredients.first_mut() else {
+        return;
+    };
+    let header_len = first.indent.rfind('\n').map_or(0, |i| i + 1);
+    let header = first.indent[..header_len].to_string();
+    first.indent = first.indent[header_len..].to_string().into();
+    // Group each ingredient with those nested under it
+    let depth = ingredients.iter().map(Ingredient::depth).min().unwrap_or(0);
+    let mut groups: Vec<Vec<Ingredient<'_>>> = vec![];
+    for ingredient in ingredients.drain(..) {
+        match groups.last_mut() {
+            Some(group) if ingredient.depth() > depth => group.push(ingredient),
+            _ => groups.push(vec![ingredient]),
+        }
+    }
+    groups.sort_by(|a, b| match by {
+        SortKey::Amount => compare_amounts(&a[0].quantity, &b[0].quantity),
+        SortKey::Name => {
+            let name = |i: &Ingredient| i.name.trim().to_lowercase();
+            name(&a[0]).cmp(&name(&b[0]))
+        }
+    });
+    for mut group in groups {
+        let mut nested = group.split_off(1);
+        sort_ingredients(&mut nested, by);
+        ingredients.extend(group);
+        ingredients.extend(nested);
+    }
+    if let Some(first) = ingredients.first_mut() {
+        first.indent = format!("{header}{}", first.indent).into();
+    }
+}
+
+// Order quantities largest first, grouping them by kind
+fn compare_amounts(a: &Quantity, b: &Quantity) -> core::cmp::Ordering {
+    let rank = |quantity: &Quantity| match quantity {
+        Quantity::Volume(volume) => (0, volume.quarter_teaspoons),
+        Quantity::Weight(weight) => (1, weight.grams),
+        Quantity::Range { high, .. } => (2, *high),
+        // Packages go by how much they hold altogether
+        Quantity::Package { count, size, .. } => match size.as_ref() {
+            Quantity::Volume(volume) => (0, volume.quarter_teaspoons * count),
+            Quantity::Weight(weight) => (1, weight.grams * count),
+            _ => (3, *count),
+        },
+        Quantity::Simple(count) | Quantity::Fixed(count) => (3, *count),
+        Quantity::ToTaste { .. } => (4, 0.0),
+        Quantity::None => (5, 0.0),
+    };
+    let ((a_rank, a_amount), (b_rank, b_amount)) = (rank(a), rank(b));
+    a_rank.cmp(&b_rank).then(b_amount.total_cmp(&a_amount))
+}
+
+#[derive(Debug, Clone)]
+pub struct ScaleOptions {
+    whole: Vec<String>,
+}
+
+impl Default for ScaleOptions {
+    fn default() -> Self {
+        ScaleOptions {
+            whole: vec!["egg".to_string()],
+        }
+    }
+}
+
+impl ScaleOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Adds an ingredient whose count is always rounded up to a whole
+    /// number, given in the singular, like 'egg'.
+    pub fn whole(mut self, name: &str) -> Self {
+        self.whole.push(name.to_lowercase());
+        self
+    }
+    fn round<'a>(&self, mut ingredient: Ingredient<'a>) -> Ingredient<'a> {
+        let name = ingredient.name.to_lowercase();
+        let noun = Pluralizer::default().singular(&name[noun_range(&name)]);
+        let is_whole = self.whole.contains(&noun);
+        if !is_whole || ingredient.fixed {
+            return ingredient;
+        }
+        match &mut ingredient.quantity {
+            Quantity::Simple(count) => *count = float::ceil(*count),
+            Quantity::Range { low, high, .. } => {
+                *low = float::ceil(*low);
+                *high = float::ceil(*high);
+            }
+            _ => (),
+        }
+        ingredient
+    }
+}
+
+/// Builds a recipe from parts, laid out the same way as a parsed one.
+///
+/// ```
+/// use maddi_recipe::{Quantity, RecipeBuilder};
+///
+/// let recipe = RecipeBuilder::new()
+///     .preface("# Toast")
+///     .add_ingredient("slice of bread", Quantity::Simple(1.0))
+///     .add_ingredient("butter", Quantity::volume(1.0, "tbsp").unwrap())
+///     .instructions("Toast the bread, then butter it.")
+///     .build();
+/// assert_eq!(recipe.title.as_deref(), Some("Toast"));
+/// assert_eq!(recipe.ingredient_count(), 2);
+/// ```
+#[derive(Debug, Clone, Default)]
+pub struct RecipeBuilder {
+    preface: String,
+    ingredients: Vec<(String, Quantity)>,
+    instructions: String,
+}
+
+impl RecipeBuilder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn preface(mut self, preface: impl Into<String>) -> Self {
+        self.preface = preface.into();
+        self
+    }
+    pub fn add_ingredient(mut self, name: impl Into<String>, quantity: Quantity) -> Self {
+        self.ingredients.push((name.into(), quantity));
+        self
+    }
+    pub fn instructions(mut self, instructions: impl Into<String>) -> Self {
+        self.instructions = instructions.into();
+        self
+    }
+    pub fn build(self) -> Recipe<'static> {
+        let mut preface = self.preface.trim_end().to_string();
+        if !preface.is_empty() {
+            preface.push_str("\n\n");
+        }
+        preface.push_str("## Ingredients\n\n");
+        let ingredients = self
+            .ingredients
+            .into_iter()
+            .map(|(name, quantity)| Ingredient {
+                indent: Cow::Borrowed(""),
+                bullet: '-',
+                bullet_spacing: None,
+                quantity,
+                amount_text: None,
+                divider: None,
+                name_separator: None,
+                name: name.into(),
+                preparation: None,
+                note: None,
+                optional: false,
+                optional_marker: None,
+                fixed: false,
+                fixed_marker: None,
+                trailing: Cow::Borrowed("\n"),
+            })
+            .collect();
+        let instructions = match self.instructions.trim() {
+            "" => String::new(),
+            instructions => format!("\n## Instructions\n\n{instructions}\n"),
+        };
+        let (front_matter, body) = split_front_matter(&preface);
         Recipe {
+            front_matter: front_matter.map(parse_front_matter),
+            title: parse_title(body).map(|t| t.to_string().into()),
+            yield_: Yield::parse(body).map(|y| y.into_static()),
+            difficulty: labeled_value(body, &["difficulty"], Difficulty::parse),
+            rating: labeled_value(body, &["rating"], parse_rating),
             preface: preface.into(),
+            has_ingredients_section: true,
             ingredients,
+            sections: vec![],
             instructions: instructions.into(),
+            notes: None,
         }
     }
 }
 
-fn parse_f32(num: &str) -> Result<f32, std::num::ParseFloatError> {
-    if let Some((a, b)) = num.split_once("/") {
-        Ok(a.parse::<f32>()? / b.parse::<f32>()?)
-    } else {
-        num.parse::<f32>()
+#[derive(Debug, Clone, Copy, PartialEq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Density {
+    grams_per_milliliter: f32,
+}
+
+impl Density {
+    pub fn from_grams_per_milliliter(grams_per_milliliter: f32) -> Self {
+        Density {
+            grams_per_milliliter,
+        }
+    }
+    pub fn from_grams_per_cup(grams_per_cup: f32) -> Self {
+        let cup = Volume::from_cups(1.0).quarter_teaspoons / quarter_teaspoons::MILLILITER;
+        Self::from_grams_per_milliliter(grams_per_cup / cup)
+    }
+    pub fn grams_per_milliliter(&self) -> f32 {
+        self.grams_per_milliliter
     }
 }
 
-impl<'a> Ingredient<'a> {
-    fn into_static(self) -> Ingredient<'static> {
-        let Self {
-            indent,
-            quantity,
-            name,
-        } = self;
-        Ingredient {
-            indent: indent.to_string().into(),
-            quantity,
-            name: name.to_string().into(),
+#[derive(Debug, Clone)]
+pub struct DensityTable {
+    densities: Vec<(String, Density)>,
+}
+
+impl Default for DensityTable {
+    fn default() -> Self {
+        let densities = [
+            ("water", Density::from_grams_per_milliliter(1.0)),
+            ("milk", Density::from_grams_per_milliliter(1.03)),
+            ("flour", Density::from_grams_per_cup(120.0)),
+            ("sugar", Density::from_grams_per_cup(200.0)),
+            ("brown sugar", Density::from_grams_per_cup(220.0)),
+            ("butter", Density::from_grams_per_cup(227.0)),
+            ("oil", Density::from_grams_per_cup(218.0)),
+            ("honey", Density::from_grams_per_cup(340.0)),
+            ("salt", Density::from_grams_per_cup(292.0)),
+        ];
+        DensityTable {
+            densities: densities
+                .into_iter()
+                .map(|(name, density)| (name.to_string(), density))
+                .collect(),
         }
     }
-    fn scale(&self, factor: f32) -> Self {
-        let quantity = match &self.quantity {
-            Quantity::None => Quantity::None,
-            Quantity::Simple(q) => Quantity::Simple(q * factor),
-            Quantity::Volume(volume) => Quantity::Volume(volume.scale(factor)),
-        };
-        Self {
-            indent: self.indent.clone(),
-            quantity,
-            name: self.name.clone(),
+}
+
+impl DensityTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn density(mut self, name: &str, density: Density) -> Self {
+        let name = name.to_lowercase();
+        self.densities.retain(|(n, _)| *n != name);
+        self.densities.push((name, density));
+        self
+    }
+    /// Finds the density for an ingredient name, matching the longest
+    /// known name it ends with, so 'of brown sugar' finds 'brown sugar'
+    /// before 'sugar'.
+    pub fn get(&self, name: &str) -> Option<Density> {
+        let name = name.trim().to_lowercase();
+        self.densities
+            .iter()
+            .filter(|(known, _)| {
+                name.strip_suffix(known.as_str())
+                    .is_some_and(|start| start.is_empty() || start.ends_with(' '))
+            })
+            .max_by_key(|(known, _)| known.len())
+            .map(|(_, density)| *density)
+    }
+}
+
+/// A line of a shopping list, totalling an ingredient across a recipe.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ShoppingItem {
+    pub name: String,
+    pub quantity: Quantity,
+    pub category: Option<String>,
+}
+
+impl Display for ShoppingItem {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        self.quantity.write_in(f, None)?;
+        write!(f, "{}", self.name)
+    }
+}
+
+/// Which aisle of the shop ingredients are found in.
+#[derive(Debug, Clone)]
+pub struct CategoryTable {
+    categories: Vec<(String, String)>,
+}
+
+impl Default for CategoryTable {
+    fn default() -> Self {
+        let categories = [
+            ("produce", "onion"),
+            ("produce", "garlic"),
+            ("produce", "tomato"),
+            ("produce", "potato"),
+            ("produce", "carrot"),
+            ("produce", "capsicum"),
+            ("produce", "mushroom"),
+            ("produce", "pineapple"),
+            ("produce", "lemon"),
+            ("produce", "lime"),
+            ("produce", "apple"),
+            ("produce", "banana"),
+            ("produce", "spinach"),
+            ("produce", "lettuce"),
+            ("dairy", "milk"),
+            ("dairy", "butter"),
+            ("dairy", "cheese"),
+            ("dairy", "cream"),
+            ("dairy", "yogurt"),
+            ("dairy", "egg"),
+            ("pantry", "flour"),
+            ("pantry", "sugar"),
+            ("pantry", "salt"),
+            ("pantry", "oil"),
+            ("pantry", "honey"),
+            ("pantry", "rice"),
+            ("pantry", "pasta"),
+            ("pantry", "sauce"),
+            ("pantry", "stock"),
+            ("pantry", "vinegar"),
+            ("pantry", "yeast"),
+            ("spices", "pepper"),
+            ("spices", "herb"),
+            ("spices", "cinnamon"),
+            ("spices", "cumin"),
+            ("spices", "paprika"),
+        ];
+        CategoryTable {
+            categories: categories
+                .into_iter()
+                .map(|(category, name)| (name.to_string(), category.to_string()))
+                .collect(),
         }
     }
-    fn parse(src: &'a str) -> Self {
-        let (indent, tail) = src
-            .split_once("- ")
-            .expect("Attempted to parse a non-ingredient string.");
-        let (quantity, name) = 'parse_quantity: {
-            // Try to parse as a volume
-            if let Some((amount, unit, name)) = tail.split_twice(" ")
-                && let Some(volume) = Volume::parse(amount, unit)
-            {
-                break 'parse_quantity (Quantity::Volume(volume), name);
-            };
-            // Try to parse as a simple
-            if let Some((amount, name)) = tail.split_once(" ")
-                && let Ok(simple) = parse_f32(amount)
-            {
-                break 'parse_quantity (Quantity::Simple(simple), name);
-            }
-            // Resort to a none
-            (Quantity::None, tail)
+}
+
+impl CategoryTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn category(mut self, name: &str, category: &str) -> Self {
+        let name = name.to_lowercase();
+        self.categories.retain(|(n, _)| *n != name);
+        self.categories.push((name, category.to_string()));
+        self
+    }
+    /// Finds the category for an ingredient name, matching the longest
+    /// known name it ends with like [`DensityTable::get`], in either the
+    /// singular or plural.
+    pub fn get(&self, name: &str) -> Option<&str> {
+        let name = name.trim().to_lowercase();
+        let (head, last) = name.rsplit_once(' ').unwrap_or(("", &name));
+        let singular = match head {
+            "" => Pluralizer::default().singular(last),
+            head => format!("{head} {}", Pluralizer::default().singular(last)),
         };
-        Self {
-            indent: indent.into(),
-            quantity,
-            name: name.into(),
-        }
+        self.categories
+            .iter()
+            .filter(|(known, _)| {
+                [&name, &singular].iter().any(|name| {
+                    name.strip_suffix(known.as_str())
+                        .is_some_and(|start| start.is_empty() || start.ends_with(' '))
+                })
+            })
+            .max_by_key(|(known, _)| known.len())
+            .map(|(_, category)| category.as_str())
     }
 }
 
-struct Ingredients<'a>(&'a str);
+#[derive(Debug, Clone)]
+pub struct ParseOptions {
+    headers: Vec<String>,
+    decimal_comma: bool,
+    name_first: bool,
+    preparation: bool,
+}
 
-impl<'a> Iterator for Ingredients<'a> {
-    type Item = &'a str;
+impl Default for ParseOptions {
+    fn default() -> Self {
+        ParseOptions {
+            headers: vec!["## Ingredients".to_string()],
+            decimal_comma: false,
+            name_first: false,
+            preparation: false,
+        }
+    }
+}
 
-    fn next(&mut self) -> Option<Self::Item> {
-        // Store the current tail
-        let src = self.0;
-        // Skip past the start of the md item
-        let (_, tail) = src.split_once("- ")?;
-        // Find the start of the next item
-        for line in tail.split("\n") {
-            if line.trim_start().starts_with("-") {
+impl ParseOptions {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    /// Sets the headers accepted as starting the ingredients, such as
+    /// `## Ingredient List`.
+    pub fn headers(mut self, headers: impl IntoIterator<Item = impl Into<String>>) -> Self {
+        self.headers = headers.into_iter().map(Into::into).collect();
+        self
+    }
+    /// Reads amounts like '0,5' as decimals, as written in much of
+    /// Europe.
+    pub fn decimal_comma(mut self, decimal_comma: bool) -> Self {
+        self.decimal_comma = decimal_comma;
+        self
+    }
+    /// Reads ingredients written name first, like 'Flour: 2 cups' or
+    /// 'Flour - 2 cups'.
+    pub fn name_first(mut self, name_first: bool) -> Self {
+        self.name_first = name_first;
+        self
+    }
+    /// Splits a trailing clause like the ', diced' in '2 apples, diced'
+    /// off the name into the ingredient's preparation.
+    pub fn preparation(mut self, preparation: bool) -> Self {
+        self.preparation = preparation;
+        self
+    }
+}
+
+impl<'a> Recipe<'a> {
+    /// Negative and NaN factors are treated as zero, as there's no such
+    /// thing as a negative amount of an ingredient.
+    pub fn scale(&self, factor: f32) -> Self {
+        Recipe {
+            preface: self.preface.clone(),
+            front_matter: self.front_matter.clone(),
+            title: self.title.clone(),
+            yield_: self.yield_.clone(),
+            difficulty: self.difficulty,
+            rating: self.rating,
+            has_ingredients_section: self.has_ingredients_section,
+            ingredients: self.ingredients.iter().map(|i| i.scale(factor)).collect(),
+            sections: self.sections.iter().map(|s| s.scale(factor)).collect(),
+            instructions: self.instructions.clone(),
+            notes: self.notes.clone(),
+        }
+    }
+    /// Like [`Recipe::scale`], but `None` for a NaN factor or if any
+    /// quantity would overflow.
+    pub fn checked_scale(&self, factor: f32) -> Option<Self> {
+        if factor.is_nan() {
+            return None;
+        }
+        let scaled = self.scale(factor);
+        let finite = scaled.all_ingredients().all(|i| i.quantity.is_finite());
+        finite.then_some(scaled)
+    }
+    /// Like [`Recipe::scale`], but rounding volumes to the nearest eighth
+    /// of a teaspoon, the smallest measuring spoon most kitchens have.
+    pub fn scale_rounded(&self, factor: f32) -> Self {
+        let step = Volume::from_teaspoons(0.125);
+        self.scale(factor).map_ingredients(|mut ingredient| {
+            if let Quantity::Volume(volume) = &ingredient.quantity {
+                ingredient.quantity = Quantity::Volume(volume.round_to_nearest(&step));
+            }
+            ingredient
+        })
+    }
+    /// Like [`Recipe::scale`], but rounding the counts of ingredients
+    /// that can't be split, like eggs, up to whole numbers.
+    pub fn scale_with_options(&self, factor: f32, options: &ScaleOptions) -> Self {
+        self.scale(factor)
+            .map_ingredients(|ingredient| options.round(ingredient))
+    }
+    /// Sorts the ingredients in each section, keeping nested ingredients
+    /// with the one they're nested under.
+    pub fn sort_ingredients(&mut self, by: SortKey) {
+        sort_ingredients(&mut self.ingredients, by);
+        for section in &mut self.sections {
+            sort_ingredients(&mut section.ingredients, by);
+        }
+    }
+    pub fn map_ingredients<F: FnMut(Ingredient<'a>) -> Ingredient<'a>>(mut self, mut f: F) -> Self {
+        self.ingredients = self.ingredients.into_iter().map(&mut f).collect();
+        for section in &mut self.sections {
+            let ingredients = core::mem::take(&mut section.ingredients);
+            section.ingredients = ingredients.into_iter().map(&mut f).collect();
+        }
+        self
+    }
+    /// Rewrites the instructions, which start with their heading.
+    pub fn map_instructions<F: FnOnce(&str) -> String>(mut self, f: F) -> Self {
+        self.instructions = f(&self.instructions).into();
+        self
+    }
+    /// Rewrites the preface, reading its title and other metadata again
+    /// from the new one.
+    pub fn map_preface<F: FnOnce(&str) -> String>(mut self, f: F) -> Self {
+        let preface = f(&self.preface);
+        let (front_matter, body) = split_front_matter(&preface);
+        self.front_matter = front_matter.map(parse_front_matter);
+        self.title = parse_title(body).map(|t| t.to_string().into());
+        self.yield_ = Yield::parse(body).map(|y| y.into_static());
+        self.difficulty = labeled_value(body, &["difficulty"], Difficulty::parse);
+        self.rating = labeled_value(body, &["rating"], parse_rating);
+        self.preface = preface.into();
+        self
+    }
+    // The merged recipe keeps the first recipe's preface, and lists every
+    // recipe's ingredients, instructions and notes in turn
+    pub fn merge(recipes: &[Recipe<'a>]) -> Self {
+        let mut merged = match recipes.first() {
+            Some(first) => Recipe {
+                ingredients: vec![],
+                sections: vec![],
+                instructions: Cow::Borrowed(""),
+                notes: None,
+                ..first.clone()
+            },
+            None => Recipe::parse(""),
+        };
+        let mut instructions = String::new();
+        let mut notes: Option<String> = None;
+        for recipe in recipes {
+            merged.has_ingredients_section |= recipe.has_ingredients_section;
+            merged
+                .ingredients
+                .extend(recipe.ingredients.iter().cloned());
+            merged.sections.extend(recipe.sections.iter().cloned());
+            if !instructions.is_empty() && !instructions.ends_with('\n') {
+                instructions.push('\n');
+            }
+            instructions.push_str(&recipe.instructions);
+            if let Some(recipe_notes) = &recipe.notes {
+                notes.get_or_insert_with(String::new).push_str(recipe_notes);
+            }
+        }
+        merged.instructions = instructions.into();
+        merged.notes = notes.map(Into::into);
+        merged
+    }
+    /// Totals up each ingredient, grouped by the category it's found in,
+    /// using the built in [`CategoryTable`].
+    pub fn shopping_list(&self) -> Vec<ShoppingItem> {
+        self.shopping_list_with(&CategoryTable::default())
+    }
+    pub fn shopping_list_with(&self, categories: &CategoryTable) -> Vec<ShoppingItem> {
+        let ingredients: Vec<_> = self.all_ingredients().collect();
+        let mut items: Vec<ShoppingItem> = vec![];
+        for (index, ingredient) in ingredients.iter().enumerate() {
+            // Skip the headings of groups, which are bought as their parts
+            let is_group = ingredients
+                .get(index + 1)
+                .is_some_and(|next| next.depth() > ingredient.depth());
+            if is_group && matches!(ingredient.quantity, Quantity::None) {
+                continue;
+            }
+            let name = ingredient.plain_name();
+            let name = name.trim();
+            let name = name.strip_prefix("of ").unwrap_or(name).to_string();
+            let existing = items.iter_mut().find_map(|item| {
+                if !item.name.eq_ignore_ascii_case(&name) {
+                    return None;
+                }
+                let quantity = item.quantity.sum(&ingredient.quantity)?;
+                Some((item, quantity))
+            });
+            match existing {
+                Some((item, quantity)) => item.quantity = quantity,
+                None => items.push(ShoppingItem {
+                    category: categories.get(&name).map(ToString::to_string),
+                    name,
+                    quantity: ingredient.quantity.clone(),
+                }),
+            }
+        }
+        // Group by category, leaving anything uncategorized until last
+        items.sort_by_key(|item| (item.category.is_none(), item.category.clone()));
+        items
+    }
+    pub fn consolidate(&self) -> Self {
+        let mut recipe = self.clone();
+        let mut lists: Vec<&mut Vec<Ingredient<'a>>> = core::iter::once(&mut recipe.ingredients)
+            .chain(recipe.sections.iter_mut().map(|s| &mut s.ingredients))
+            .collect();
+        // Fold each ingredient into the first compatible one of the same
+        // name, wherever in the recipe it appears
+        let mut kept: Vec<(usize, usize)> = vec![];
+        for list in 0..lists.len() {
+            let mut i = 0;
+            while i < lists[list].len() {
+                let ingredient = &lists[list][i];
+                let name = ingredient.name.trim().to_lowercase();
+                let combined = kept.iter().find_map(|&(l, k)| {
+                    let other = &lists[l][k];
+                    if other.name.trim().to_lowercase() != name {
+                        return None;
+                    }
+                    Some(((l, k), other.quantity.sum(&ingredient.quantity)?))
+                });
+                match combined {
+                    Some(((l, k), quantity)) => {
+                        lists[l][k].quantity = quantity;
+                        lists[list].remove(i);
+                    }
+                    None => {
+                        kept.push((list, i));
+                        i += 1;
+                    }
+                }
+            }
+        }
+        recipe
+    }
+    pub fn scale_to_servings(&self, current: u32, target: u32) -> Self {
+        // A recipe serving nobody can't be scaled meaningfully
+        if current == 0 {
+            return self.clone();
+        }
+        self.scale(target as f32 / current as f32)
+    }
+    pub fn scale_to(&self, target: u32) -> Option<Self> {
+        Some(self.scale_to_servings(self.servings()?, target))
+    }
+    pub fn servings(&self) -> Option<u32> {
+        self.yield_.as_ref().map(|y| y.count)
+    }
+    #[cfg(feature = "std")]
+    pub fn from_reader<R: std::io::Read>(mut reader: R) -> std::io::Result<Recipe<'static>> {
+        let mut src = String::new();
+        reader.read_to_string(&mut src)?;
+        let recipe = Recipe::try_parse(&src)
+            .map_err(|e| std::io::Error::new(std::io::ErrorKind::InvalidData, e))?;
+        Ok(recipe.into_static())
+    }
+    /// Parses a recipe, keeping any lines of the ingredients that can't be
+    /// read with the ingredient before or after them, so they're still
+    /// written back as they were. Use [`Recipe::try_parse`] to find out
+    /// what was wrong instead.
+    pub fn parse(src: &'a str) -> Self {
+        Self::parse_recovering(src, &ParseOptions::default(), true)
+            .unwrap_or_else(|_| Self::preface_only(src))
+    }
+    // A recipe without any ingredients or instructions, where everything
+    // is part of the preface
+    fn preface_only(src: &'a str) -> Self {
+        let (front_matter, body) = split_front_matter(src);
+        Recipe {
+            preface: Cow::Borrowed(src),
+            front_matter: front_matter.map(parse_front_matter),
+            title: parse_title(body).map(Into::into),
+            yield_: Yield::parse(body),
+            difficulty: labeled_value(body, &["difficulty"], Difficulty::parse),
+            rating: labeled_value(body, &["rating"], parse_rating),
+            has_ingredients_section: false,
+            ingredients: vec![],
+            sections: vec![],
+            instructions: Cow::Borrowed(""),
+            notes: None,
+        }
+    }
+    pub fn try_parse(src: &'a str) -> Result<Self, ParseError> {
+        Self::parse_with_options(src, &ParseOptions::default())
+    }
+    pub fn parse_with_options(src: &'a str, options: &ParseOptions) -> Result<Self, ParseError> {
+        Self::parse_recovering(src, options, false)
+    }
+    // Parse a recipe, either failing on the first malformed ingredient or
+    // recovering by keeping its text with the ingredients around it
+    fn parse_recovering(
+        src: &'a str,
+        options: &ParseOptions,
+        recover: bool,
+    ) -> Result<Self, ParseError> {
+        // Find where the ingredients start, under whichever header comes first
+        let found = options
+            .headers
+            .iter()
+            .filter_map(|header| {
+                // Allow for trailing whitespace and Windows line endings
+                // after the header, and for the blank line to be missing
+                let header = format!("\n{header}");
+                src.match_indices(&header).find_map(|(start, _)| {
+                    let tail = src[start + header.len()..].trim_start_matches([' ', '\t']);
+                    let strip_newline =
+                        |s: &'a str| s.strip_prefix('\n').or(s.strip_prefix("\r\n"));
+                    let tail = strip_newline(tail)?;
+                    let tail = strip_newline(tail).unwrap_or(tail);
+                    Some(src.len() - tail.len())
+                })
+            })
+            .min();
+        let Some(ingredients_start) = found else {
+            return Ok(Self::preface_only(src));
+        };
+        // Seperate the preface, ingredients, and instructions
+        let document = src;
+        let (mut preface, src) = src.split_at(ingredients_start);
+        let (block, mut src) = split_at_heading(src);
+        let ingredients =
+            parse_ingredients(block, options, recover).map_err(|e| e.after(document, block))?;
+        // Without a single ingredient to keep it with, the block is part of
+        // the preface
+        if ingredients.is_empty() && !block.trim().is_empty() {
+            preface = &document[..ingredients_start + block.len()];
+        }
+        // Parse any further titled ingredient sections
+        let mut sections = vec![];
+        while let Some((heading, title, tail)) = split_subsection_heading(src) {
+            let (ingredients, tail) = split_at_heading(tail);
+            let ingredients = parse_ingredients(ingredients, options, recover)
+                .map_err(|e| e.after(document, ingredients))?;
+            sections.push(IngredientSection {
+                heading: heading.into(),
+                title: title.into(),
+                ingredients,
+            });
+            src = tail;
+        }
+
+        let (instructions, notes) = split_notes(src);
+
+        // Return the recipe
+        let (front_matter, body) = split_front_matter(preface);
+        Ok(Recipe {
+            preface: preface.into(),
+            front_matter: front_matter.map(parse_front_matter),
+            title: parse_title(body).map(Into::into),
+            yield_: Yield::parse(body),
+            difficulty: labeled_value(body, &["difficulty"], Difficulty::parse),
+            rating: labeled_value(body, &["rating"], parse_rating),
+            has_ingredients_section: true,
+            ingredients,
+            sections,
+            instructions: instructions.into(),
+            notes: notes.map(Into::into),
+        })
+    }
+}
+
+// Measure the marker at the start of a step, either a numbered list
+// item or a task list item, noting whether a task was checked
+fn step_marker(line: &str) -> Option<(usize, Option<bool>)> {
+    if let Some(len) = step_number_len(line) {
+        return Some((len, None));
+    }
+    let trimmed = line.trim_start();
+    let task = trimmed.strip_prefix(['-', '*', '+'])?.strip_prefix(" [")?;
+    let checked = match task.chars().next()? {
+        ' ' => false,
+        'x' | 'X' => true,
+        _ => return None,
+    };
+    let rest = task[1..].strip_prefix(']')?;
+    if !rest.is_empty() && !rest.starts_with(' ') {
+        return None;
+    }
+    Some((line.len() - rest.len(), Some(checked)))
+}
+
+// Measure the '1. ' marker at the start of a numbered list item
+fn step_number_len(line: &str) -> Option<usize> {
+    let trimmed = line.trim_start();
+    let digits = trimmed.len()
+        - trimmed
+            .trim_start_matches(|c: char| c.is_ascii_digit())
+            .len();
+    let marker = trimmed[digits..].strip_prefix(['.', ')'])?;
+    if digits == 0 || !marker.starts_with(' ') {
+        return None;
+    }
+    Some(line.len() - marker.len() + 1)
+}
+
+// Split a '---' fenced front-matter block off the start of the
+// preface, returning its contents and the rest of the preface
+fn split_front_matter(preface: &str) -> (Option<&str>, &str) {
+    let Some(block) = preface
+        .strip_prefix("---\n")
+        .or_else(|| preface.strip_prefix("---\r\n"))
+    else {
+        return (None, preface);
+    };
+    let mut offset = 0;
+    for line in block.split_inclusive('\n') {
+        if line.trim_end() == "---" {
+            let rest = &block[offset + line.len()..];
+            return (Some(&block[..offset]), rest);
+        }
+        offset += line.len();
+    }
+    (None, preface)
+}
+
+// Read front matter's 'key: value' lines, leaving values as written
+fn parse_front_matter(block: &str) -> BTreeMap<String, String> {
+    block
+        .lines()
+        .filter_map(|line| line.split_once(':'))
+        .map(|(key, value)| (key.trim().to_string(), value.trim().to_string()))
+        .filter(|(key, _)| !key.is_empty())
+        .collect()
+}
+
+const PREP_LABELS: [&str; 4] = ["prep time", "preparation time", "preparation", "prep"];
+const COOK_LABELS: [&str; 4] = ["cook time", "cooking time", "cooking", "cook"];
+
+// Find the first line starting with one of the labels and a ':', like
+// 'Prep: 20 min', that the value after it can be read from
+fn labeled_value<T>(
+    preface: &str,
+    labels: &[&str],
+    parse: impl Fn(&str) -> Option<T>,
+) -> Option<T> {
+    preface.lines().find_map(|line| {
+        let line = line.trim_start_matches(['-', '*', '_', ' ']);
+        let tail = labels.iter().find_map(|label| {
+            let head = line.get(..label.len())?;
+            head.eq_ignore_ascii_case(label)
+                .then(|| line[label.len()..].strip_prefix(':'))?
+        })?;
+        parse(tail.trim_start_matches(['*', '_']))
+    })
+}
+
+// Read ratings like '4/5', '0.8' or '★★★★☆' as a fraction of full marks
+fn parse_rating(src: &str) -> Option<f32> {
+    let src = src.trim();
+    if !src.is_empty() && src.chars().all(|c| c == '★' || c == '☆') {
+        let stars = src.chars().filter(|&c| c == '★').count();
+        return Some(stars as f32 / src.chars().count() as f32);
+    }
+    parse_amount(src)
+        .ok()
+        .filter(|rating| (0.0..=1.0).contains(rating))
+}
+
+// Read durations like '1 hr 15 min', '90 minutes' or '1h15m'
+fn parse_duration(src: &str) -> Option<Duration> {
+    let mut minutes = 0.0;
+    let mut found = false;
+    let mut amount = None;
+    // Split numbers from the units written against them
+    let mut words = vec![];
+    for word in src.split_whitespace() {
+        let mut rest = word;
+        while !rest.is_empty() {
+            let split = match rest.find(|c: char| c.is_alphabetic()) {
+                Some(0) => rest
+                    .find(|c: char| !c.is_alphabetic())
+                    .unwrap_or(rest.len()),
+                Some(split) => split,
+                None => rest.len(),
+            };
+            words.push(&rest[..split]);
+            rest = &rest[split..];
+        }
+    }
+    for word in words {
+        let word = word.trim_end_matches([',', '.']);
+        if let Ok(number) = parse_amount(word) {
+            amount = Some(number);
+            continue;
+        }
+        let unit_minutes = match word.to_lowercase().as_str() {
+            "h" | "hr" | "hrs" | "hour" | "hours" => 60.0,
+            "m" | "min" | "mins" | "minute" | "minutes" => 1.0,
+            _ => continue,
+        };
+        minutes += amount.take()? * unit_minutes;
+        found = true;
+    }
+    found.then(|| Duration::from_secs(float::round(minutes * 60.0) as u64))
+}
+
+// Find the recipe's title in the first '# ' heading of its preface
+fn parse_title(preface: &str) -> Option<&str> {
+    let line = preface.lines().find(|line| !line.trim().is_empty())?;
+    Some(line.strip_prefix("# ")?.trim())
+}
+
+// Split a trailing '## Notes' section off the end of the instructions
+fn split_notes(src: &str) -> (&str, Option<&str>) {
+    let notes_start = src.match_indices("\n## ").find_map(|(start, heading)| {
+        let line = src[start + heading.len()..].lines().next()?;
+        line.trim().eq_ignore_ascii_case("notes").then_some(start)
+    });
+    match notes_start {
+        Some(start) => (&src[..start], Some(&src[start..])),
+        None => (src, None),
+    }
+}
+
+// Split a block of markdown at the start of its next '##' heading
+fn split_at_heading(src: &str) -> (&str, &str) {
+    if src.starts_with("##") {
+        return ("", src);
+    }
+    match src.find("\n##") {
+        Some(end) => src.split_at(end),
+        None => (src, ""),
+    }
+}
+
+// Split an ingredient subsection's heading (and any blank lines after
+// it) off the front of a block, returning the heading, its title, and
+// the rest of the block. Subsections are either '###' (or deeper)
+// headings, or '##' headings starting with 'For', like '## For the Dough',
+// followed by a bulleted item. Other headings, like '### Tips', end the
+// ingredients.
+fn split_subsection_heading(src: &str) -> Option<(&str, &str, &str)> {
+    let line_start = src.len() - src.trim_start_matches(['\r', '\n']).len();
+    let line = src[line_start..].split('\n').next()?;
+    let title = if line.starts_with("###") {
+        line.trim_start_matches('#').trim()
+    } else {
+        let title = line.strip_prefix("## ")?.trim();
+        if !title.to_lowercase().starts_with("for ") {
+            return None;
+        }
+        title
+    };
+    let mut heading_end = line_start + line.len();
+    heading_end +=
+        src[heading_end..].len() - src[heading_end..].trim_start_matches(['\r', '\n']).len();
+    let (heading, tail) = src.split_at(heading_end);
+    leading_bullet(tail.lines().next()?)?;
+    Some((heading, title, tail))
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum ParseError {
+    /// A line in the ingredients that isn't part of any bulleted item,
+    /// along with its line number in the source, counting from 1.
+    MissingBullet { line: String, line_number: usize },
+}
+
+impl ParseError {
+    /// The line of the source the error was found on, counting from 1.
+    pub fn line_number(&self) -> usize {
+        match self {
+            ParseError::MissingBullet { line_number, .. } => *line_number,
+        }
+    }
+    // Move the error down by the lines in front of the part it was found
+    // in, so it counts from the start of the whole source
+    fn after(mut self, src: &str, part: &str) -> Self {
+        let offset = part.as_ptr() as usize - src.as_ptr() as usize;
+        let lines = src[..offset].matches('\n').count();
+        match &mut self {
+            ParseError::MissingBullet { line_number, .. } => *line_number += lines,
+        }
+        self
+    }
+}
+
+impl Display for ParseError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            ParseError::MissingBullet { line, line_number } => {
+                write!(
+                    f,
+                    "line {line_number}: expected an ingredient bullet, found '{line}'"
+                )
+            }
+        }
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+/// The error from [`parse_amount`] for text that isn't an amount.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct AmountError;
+
+impl Display for AmountError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "expected an amount like '2', '3/4' or '1 1/2'")
+    }
+}
+
+impl core::error::Error for AmountError {}
+
+/// Parses an amount the way recipes write them, including fractions like
+/// '3/4', mixed numbers like '1 1/2', unicode fractions like '1½' and
+/// small written numbers like 'two'.
+///
+/// ```
+/// use maddi_recipe::{AmountError, parse_amount};
+///
+/// assert_eq!(parse_amount("3/4"), Ok(0.75));
+/// assert_eq!(parse_amount("1"), Ok(1.0));
+/// assert_eq!(parse_amount("1 1/2"), Ok(1.5));
+/// assert_eq!(parse_amount("a pinch"), Err(AmountError));
+/// assert_eq!(parse_amount("inf"), Err(AmountError));
+/// ```
+pub fn parse_amount(num: &str) -> Result<f32, AmountError> {
+    // Rust happily reads 'inf', 'NaN' and '1/0', but none of them are amounts
+    parse_number(num)
+        .ok()
+        .filter(|number| number.is_finite())
+        .ok_or(AmountError)
+}
+
+fn parse_number(num: &str) -> Result<f32, core::num::ParseFloatError> {
+    // Handle unicode fractions, optionally following a whole number
+    if let Some(last) = num.chars().last()
+        && let Some(fraction) = vulgar_fraction(last)
+    {
+        let whole = num[..num.len() - last.len_utf8()].trim();
+        return match whole {
+            "" => Ok(fraction),
+            whole => Ok(whole.parse::<f32>()? + fraction),
+        };
+    }
+    if let Some(number) = written_number(num) {
+        return Ok(number);
+    }
+    // Handle mixed numbers like '1 1/2'
+    if let Some((whole, fraction)) = num.split_once(' ')
+        && fraction.contains('/')
+    {
+        return Ok(whole.parse::<f32>()? + parse_number(fraction)?);
+    }
+    if let Some((a, b)) = num.split_once("/") {
+        Ok(a.parse::<f32>()? / b.parse::<f32>()?)
+    } else {
+        num.parse::<f32>()
+    }
+}
+
+fn written_number(num: &str) -> Option<f32> {
+    const NUMBERS: [&str; 12] = [
+        "one", "two", "three", "four", "five", "six", "seven", "eight", "nine", "ten", "eleven",
+        "twelve",
+    ];
+    let num = num.to_lowercase();
+    let index = NUMBERS.iter().position(|n| *n == num)?;
+    Some(index as f32 + 1.0)
+}
+
+// Fractions written as words are only amounts when they come before a
+// unit, so things like 'half and half' aren't mistaken for quantities
+fn written_fraction(amount: &str) -> Option<f32> {
+    let amount = amount.to_lowercase();
+    let amount = ["a", "an"]
+        .iter()
+        .find_map(|article| amount.strip_suffix(article)?.strip_suffix(' '))
+        .unwrap_or(&amount);
+    match amount {
+        "half" => Some(1.0 / 2.0),
+        "third" => Some(1.0 / 3.0),
+        "quarter" => Some(1.0 / 4.0),
+        _ => None,
+    }
+}
+
+// Parse the amount in front of a unit, which may also be a fraction
+// written as a word. Volumes and weights can't be negative, so amounts
+// like '-2' are left for the caller to read some other way.
+fn parse_unit_amount(amount: &str) -> Option<f32> {
+    if amount.starts_with('-') {
+        return None;
+    }
+    written_fraction(amount).or_else(|| parse_amount(amount).ok())
+}
+
+// The unicode vulgar fractions, with their numerators and denominators
+const VULGAR_FRACTIONS: [(char, u32, u32); 15] = [
+    ('½', 1, 2),
+    ('⅓', 1, 3),
+    ('⅔', 2, 3),
+    ('¼', 1, 4),
+    ('¾', 3, 4),
+    ('⅕', 1, 5),
+    ('⅖', 2, 5),
+    ('⅗', 3, 5),
+    ('⅘', 4, 5),
+    ('⅙', 1, 6),
+    ('⅚', 5, 6),
+    ('⅛', 1, 8),
+    ('⅜', 3, 8),
+    ('⅝', 5, 8),
+    ('⅞', 7, 8),
+];
+
+fn vulgar_fraction(c: char) -> Option<f32> {
+    let &(_, numerator, denominator) = VULGAR_FRACTIONS.iter().find(|(glyph, _, _)| *glyph == c)?;
+    Some(numerator as f32 / denominator as f32)
+}
+
+// Parse an amount like '2-3' or '2 – 3', returning the separator used
+// along with the spaces around it
+fn parse_range(amount: &str) -> Option<(f32, String, f32)> {
+    let (low, high) = amount.split_once(['-', '–'])?;
+    let (low, high) = (low.trim_end(), high.trim_start());
+    let separator = &amount[low.len()..amount.len() - high.len()];
+    Some((
+        parse_amount(low).ok()?,
+        separator.to_string(),
+        parse_amount(high).ok()?,
+    ))
+}
+
+// Split a range written with spaces around its dash, like '2 - 3', off
+// the front of an ingredient
+fn split_spaced_range(src: &str) -> Option<(&str, &str)> {
+    let dash = src.find(['-', '–'])?;
+    let low = src[..dash].trim_end();
+    let after = &src[dash..];
+    let after = after[after.chars().next()?.len_utf8()..].trim_start();
+    let (high, rest) = after.split_once(' ')?;
+    let amount = &src[..src.len() - after.len() + high.len()];
+    if !amount.contains(' ') || low.is_empty() || parse_amount(low).is_err() {
+        return None;
+    }
+    parse_amount(high).ok()?;
+    Some((amount, rest))
+}
+
+// Split the amount off the front of an ingredient, keeping a lone
+// fraction like the '⅓' in '2 ⅓ cups' together with its whole number
+fn split_amount(src: &str) -> Option<(&str, &str)> {
+    if let Some(range) = split_spaced_range(src) {
+        return Some(range);
+    }
+    let (amount, rest) = src.split_once(" ")?;
+    // Keep the article in amounts like 'half a cup' too
+    if written_fraction(amount).is_some()
+        && let Some((article, rest)) = rest.split_once(" ")
+        && matches!(article.to_lowercase().as_str(), "a" | "an")
+    {
+        return Some((&src[..amount.len() + 1 + article.len()], rest));
+    }
+    // Keep the fraction in mixed numbers like '1 1/2'
+    if amount.chars().all(|c| c.is_ascii_digit())
+        && let Some((fraction, rest)) = rest.split_once(" ")
+        && let Some((numerator, denominator)) = fraction.split_once('/')
+        && [numerator, denominator]
+            .iter()
+            .all(|n| !n.is_empty() && n.chars().all(|c| c.is_ascii_digit()))
+    {
+        return Some((&src[..amount.len() + 1 + fraction.len()], rest));
+    }
+    let mut chars = rest.chars();
+    if let Some(fraction) = chars.next()
+        && vulgar_fraction(fraction).is_some()
+        && let Some(rest) = chars.as_str().strip_prefix(" ")
+    {
+        let len = amount.len() + 1 + fraction.len_utf8();
+        return Some((&src[..len], rest));
+    }
+    Some((amount, rest))
+}
+
+// Clamp a scale factor to something that won't produce negative or NaN
+// amounts, which can't be measured or displayed sensibly
+fn scale_factor(factor: f32) -> f32 {
+    if factor.is_nan() {
+        0.0
+    } else {
+        factor.max(0.0)
+    }
+}
+
+impl Quantity {
+    /// A volume of `amount` in a unit like `"cups"` or `"tbsp"`, or `None`
+    /// if the unit isn't a known volume.
+    pub fn volume(amount: f32, unit: &str) -> Option<Quantity> {
+        let (unit_quarter_teaspoons, metric) = Volume::unit_quarter_teaspoons(unit)?;
+        Some(Quantity::Volume(Volume {
+            metric,
+            ..Volume::from_quarter_teaspoons(amount * unit_quarter_teaspoons)
+        }))
+    }
+    /// A weight of `amount` in a unit like `"g"` or `"lb"`, or `None` if
+    /// the unit isn't a known weight.
+    pub fn weight(amount: f32, unit: &str) -> Option<Quantity> {
+        let (unit_grams, imperial) = Weight::unit_grams(unit)?;
+        Some(Quantity::Weight(Weight {
+            grams: amount * unit_grams,
+            imperial,
+        }))
+    }
+    pub fn is_scalable(&self) -> bool {
+        !matches!(self, Quantity::Fixed(_))
+    }
+    /// Negative and NaN factors are treated as zero. Fixed quantities
+    /// are left as they are.
+    pub fn scale(&self, factor: f32) -> Quantity {
+        let factor = scale_factor(factor);
+        match self {
+            Quantity::None => Quantity::None,
+            Quantity::ToTaste { comma } => Quantity::ToTaste { comma: *comma },
+            Quantity::Simple(q) => Quantity::Simple(q * factor),
+            Quantity::Fixed(q) => Quantity::Fixed(*q),
+            Quantity::Volume(volume) => Quantity::Volume(volume.scale(factor)),
+            Quantity::Weight(weight) => Quantity::Weight(weight.scale(factor)),
+            Quantity::Range {
+                low,
+                high,
+                separator,
+                unit,
+            } => Quantity::Range {
+                low: low * factor,
+                high: high * factor,
+                separator: separator.clone(),
+                unit: unit.clone(),
+            },
+            Quantity::Package { count, size, unit } => Quantity::Package {
+                count: count * factor,
+                size: size.clone(),
+                unit: unit.clone(),
+            },
+        }
+    }
+    // Write the amount with a space after it, or nothing if there isn't one
+    fn write_in(
+        &self,
+        f: &mut impl core::fmt::Write,
+        system: Option<UnitSystem>,
+    ) -> core::fmt::Result {
+        match self {
+            Quantity::Simple(q) | Quantity::Fixed(q) => write!(f, "{q} "),
+            // An empty volume writes nothing, like an unmeasured ingredient
+            Quantity::Volume(v) if v.is_zero() => Ok(()),
+            Quantity::Volume(v) => match system {
+                None => write!(f, "{v} "),
+                Some(UnitSystem::Imperial) => {
+                    write!(f, "{} ", VolumeFormatter::default().format(v))
+                }
+                Some(UnitSystem::Metric) => write!(f, "{} ", v.to_metric_string()),
+            },
+            Quantity::Weight(w) => write!(f, "{w} "),
+            Quantity::Range {
+                low,
+                high,
+                separator,
+                unit,
+            } => {
+                write!(f, "{low}{separator}{high} ")?;
+                if let Some(unit) = unit {
+                    write!(f, "{unit} ")?;
+                }
+                Ok(())
+            }
+            Quantity::Package { count, size, unit } => {
+                let mut size_text = String::new();
+                size.write_in(&mut size_text, system)?;
+                write!(f, "{count} ({}) {unit} ", size_text.trim_end())
+            }
+            _ => Ok(()),
+        }
+    }
+    fn is_finite(&self) -> bool {
+        match self {
+            Quantity::Simple(count) | Quantity::Fixed(count) => count.is_finite(),
+            Quantity::Volume(volume) => volume.quarter_teaspoons.is_finite(),
+            Quantity::Weight(weight) => weight.grams.is_finite(),
+            Quantity::Range { low, high, .. } => low.is_finite() && high.is_finite(),
+            Quantity::Package { count, size, .. } => count.is_finite() && size.is_finite(),
+            Quantity::None | Quantity::ToTaste { .. } => true,
+        }
+    }
+    fn sum(&self, other: &Quantity) -> Option<Quantity> {
+        let sum = match (self, other) {
+            (Quantity::Simple(a), Quantity::Simple(b)) => Quantity::Simple(a + b),
+            (Quantity::Volume(a), Quantity::Volume(b)) => Quantity::Volume(a.clone() + b.clone()),
+            (Quantity::Weight(a), Quantity::Weight(b)) => Quantity::Weight(Weight {
+                grams: a.grams + b.grams,
+                imperial: a.imperial,
+            }),
+            (
+                Quantity::Package { count, size, unit },
+                Quantity::Package {
+                    count: other_count,
+                    size: other_size,
+                    unit: other_unit,
+                },
+            ) if size == other_size && unit == other_unit => Quantity::Package {
+                count: count + other_count,
+                size: size.clone(),
+                unit: unit.clone(),
+            },
+            _ => return None,
+        };
+        Some(sum)
+    }
+}
+
+// Parse the amount at the start of an ingredient, returning its quantity,
+// the rest of the ingredient, and how a simple amount was written
+fn parse_quantity<'s>(
+    tail: &'s str,
+    options: &ParseOptions,
+) -> (Quantity, &'s str, Option<&'s str>) {
+    let Some((amount, rest)) = split_amount(tail) else {
+        return (Quantity::None, tail, None);
+    };
+    let written = amount;
+    let decimal;
+    let amount = match decimal_comma(amount) {
+        Some(comma) if options.decimal_comma => {
+            decimal = format!("{}.{}", &amount[..comma], &amount[comma + 1..]);
+            decimal.as_str()
+        }
+        _ => amount,
+    };
+    // Try to parse amounts written against their unit, like '250g'
+    if let Some(unit_start) = amount.find(char::is_alphabetic)
+        && unit_start > 0
+    {
+        let (number, unit) = amount.split_at(unit_start);
+        if let Some(volume) = Volume::parse(number, unit) {
+            return (Quantity::Volume(volume), rest, None);
+        }
+        if let Some(weight) = Weight::parse(number, unit) {
+            return (Quantity::Weight(weight), rest, None);
+        }
+    }
+    // Try to parse as a count of packages, like '3 (14 oz) cans'
+    if let Some((package, name)) = parse_package(amount, rest) {
+        return (package, name, None);
+    }
+    // Try to parse as a range, keeping the unit if it's one we know
+    if let Some((low, separator, high)) = parse_range(amount) {
+        if let Some((unit, name)) = rest.split_once(" ")
+            && (Volume::parse("1", unit).is_some() || Weight::parse("1", unit).is_some())
+        {
+            let unit = Some(unit.to_string());
+            let range = Quantity::Range {
+                low,
+                high,
+                separator,
+                unit,
+            };
+            return (range, name, None);
+        }
+        let range = Quantity::Range {
+            low,
+            high,
+            separator,
+            unit: None,
+        };
+        return (range, rest, None);
+    }
+    // Try to parse as a volume
+    for (unit, name) in split_units(rest) {
+        if let Some(volume) = Volume::parse(amount, unit) {
+            return (Quantity::Volume(volume), name, None);
+        }
+    }
+    // Try to parse as a weight
+    if let Some((unit, name)) = rest.split_once(" ")
+        && let Some(weight) = Weight::parse(amount, unit)
+    {
+        return (Quantity::Weight(weight), name, None);
+    };
+    // Try to parse as a simple
+    if let Ok(simple) = parse_amount(amount) {
+        // Plain numbers already write back as they were written
+        let amount_text = Some(written).filter(|w| *w != simple.to_string());
+        return (Quantity::Simple(simple), rest, amount_text);
+    }
+    // Resort to a none
+    (Quantity::None, tail, None)
+}
+
+// Split an ingredient written name first, like 'Flour: 2 cups' or
+// 'Flour - 2 cups', into its name, the separator and its quantity
+fn split_name_first<'s>(
+    src: &'s str,
+    options: &ParseOptions,
+) -> Option<(&'s str, &'s str, Quantity, Option<&'s str>)> {
+    let name_end = src.find(':').or_else(|| src.find(" - "))?;
+    let amount = src[name_end..]
+        .trim_start_matches([':', ' ', '-'])
+        .trim_start();
+    let separator = &src[name_end..src.len() - amount.len()];
+    // Amounts are only read in front of a name, so stand one in
+    let with_name = format!("{amount} _");
+    let (quantity, rest, amount_text) = parse_quantity(&with_name, options);
+    if matches!(quantity, Quantity::None) || rest != "_" {
+        return None;
+    }
+    let amount_text = amount_text.map(|text| &amount[..text.len()]);
+    Some((&src[..name_end], separator, quantity, amount_text))
+}
+
+// Read a package's size and unit, like the '(14 oz) cans' following the
+// count in '3 (14 oz) cans tomatoes', returning it with the rest of the name
+fn parse_package<'s>(amount: &str, rest: &'s str) -> Option<(Quantity, &'s str)> {
+    const PACKAGES: [&str; 12] = [
+        "bag",
+        "block",
+        "bottle",
+        "box",
+        "can",
+        "carton",
+        "container",
+        "jar",
+        "pack",
+        "package",
+        "packet",
+        "tin",
+    ];
+    let count = parse_amount(amount).ok()?;
+    let (size, tail) = rest.strip_prefix('(')?.split_once(") ")?;
+    let unit_start = size.find(char::is_alphabetic)?;
+    let (size_amount, size_unit) = size.split_at(unit_start);
+    let (size_amount, size_unit) = (size_amount.trim(), size_unit.trim());
+    let size = match Volume::parse(size_amount, size_unit) {
+        Some(volume) => Quantity::Volume(volume),
+        None => Quantity::Weight(Weight::parse(size_amount, size_unit)?),
+    };
+    let (unit, name) = tail.split_once(' ').unwrap_or((tail, ""));
+    if !PACKAGES.contains(&Pluralizer::default().singular(unit).as_str()) {
+        return None;
+    }
+    let package = Quantity::Package {
+        count,
+        size: Box::new(size),
+        unit: unit.to_string(),
+    };
+    Some((package, name))
+}
+
+// Find the comma in an amount like '0,5', between two digits
+fn decimal_comma(amount: &str) -> Option<usize> {
+    let comma = amount.find(',')?;
+    let before = amount[..comma].chars().next_back()?;
+    let after = amount[comma + 1..].chars().next()?;
+    (before.is_ascii_digit() && after.is_ascii_digit()).then_some(comma)
+}
+
+// Split the unit off the front of an ingredient's name, trying both one
+// and two word units so things like 'fl oz' can be recognized
+fn split_units(src: &str) -> impl Iterator<Item = (&str, &str)> {
+    let one_word = src.split_once(" ");
+    let two_words = one_word.and_then(|(first, rest)| {
+        let (second, name) = rest.split_once(" ")?;
+        Some((&src[..first.len() + 1 + second.len()], name))
+    });
+    one_word.into_iter().chain(two_words)
+}
+
+// Split a trailing parenthetical like the '(sifted)' in 'flour (sifted)'
+// off an ingredient's name
+fn split_note(name: &str) -> (&str, Option<&str>) {
+    if let Some(inner) = name.strip_suffix(')')
+        && let Some((name, note)) = inner.rsplit_once(" (")
+        && !note.contains(['(', ')'])
+    {
+        return (name, Some(note));
+    }
+    (name, None)
+}
+
+// Find a '[text](target)' markdown link, returning where it is in the
+// source along with its text and target
+fn find_link(src: &str) -> Option<(core::ops::Range<usize>, &str, &str)> {
+    let open = src.find('[')?;
+    let close = open + src[open..].find("](")?;
+    let end = close + src[close..].find(')')?;
+    Some((open..end + 1, &src[open + 1..close], &src[close + 2..end]))
+}
+
+// Split a trailing 'to taste' or ', to taste' off an ingredient's name,
+// noting whether it had a comma
+fn split_to_taste(name: &str) -> Option<(&str, bool)> {
+    let head = name.strip_suffix(" to taste")?;
+    match head.strip_suffix(',') {
+        Some(head) => Some((head, true)),
+        None => Some((head, false)),
+    }
+}
+
+impl<'a> Ingredient<'a> {
+    pub fn into_owned(self) -> Ingredient<'static> {
+        self.into_static()
+    }
+    fn into_static(self) -> Ingredient<'static> {
+        let Self {
+            indent,
+            bullet,
+            bullet_spacing,
+            quantity,
+            amount_text,
+            divider,
+            name_separator,
+            name,
+            preparation,
+            note,
+            optional,
+            optional_marker,
+            fixed,
+            fixed_marker,
+            trailing,
+        } = self;
+        Ingredient {
+            indent: indent.to_string().into(),
+            bullet,
+            bullet_spacing: bullet_spacing.map(|s| s.to_string().into()),
+            quantity,
+            amount_text: amount_text.map(|a| a.to_string().into()),
+            divider: divider.map(|d| d.to_string().into()),
+            name_separator: name_separator.map(|s| s.to_string().into()),
+            name: name.to_string().into(),
+            preparation: preparation.map(|p| p.to_string().into()),
+            note: note.map(|n| n.to_string().into()),
+            optional,
+            optional_marker: optional_marker.map(|m| m.to_string().into()),
+            fixed,
+            fixed_marker: fixed_marker.map(|m| m.to_string().into()),
+            trailing: trailing.to_string().into(),
+        }
+    }
+    fn write_in(
+        &self,
+        f: &mut impl core::fmt::Write,
+        system: Option<UnitSystem>,
+    ) -> core::fmt::Result {
+        let spacing = self.bullet_spacing.as_deref().unwrap_or(" ");
+        write!(f, "{}{}{spacing}", self.indent, self.bullet)?;
+        let marker = match (self.optional, &self.optional_marker) {
+            (false, _) => None,
+            (true, Some(marker)) => Some(marker.as_ref()),
+            (true, None) => Some(" (optional)"),
+        };
+        let leading_marker = marker.filter(|m| m.trim_end().ends_with(':'));
+        if let Some(marker) = leading_marker {
+            write!(f, "{marker}")?;
+        }
+        let mut amount = String::new();
+        match (&self.quantity, &self.amount_text) {
+            (Quantity::Simple(count), Some(text)) if parse_amount(text) == Ok(*count) => {
+                amount.push_str(text);
+                amount.push(' ');
+            }
+            _ => self.write_amount(&mut amount, system)?,
+        }
+        if self.name_separator.is_none() {
+            write!(f, "{amount}")?;
+        }
+        if let Some(divider) = &self.divider {
+            write!(f, "{divider}")?;
+        }
+        write!(f, "{}", self.name)?;
+        if let Some(preparation) = &self.preparation {
+            write!(f, ", {preparation}")?;
+        }
+        if let Quantity::ToTaste { comma } = self.quantity {
+            write!(f, "{} to taste", if comma { "," } else { "" })?;
+        }
+        if let Some(note) = &self.note {
+            write!(f, " ({note})")?;
+        }
+        if leading_marker.is_none()
+            && let Some(marker) = marker
+        {
+            write!(f, "{marker}")?;
+        }
+        if let Some(separator) = &self.name_separator {
+            write!(f, "{separator}{}", amount.trim_end())?;
+        }
+        if self.fixed {
+            write!(f, "{}", self.fixed_marker.as_deref().unwrap_or(" {fixed}"))?;
+        }
+        write!(f, "{}", self.trailing)
+    }
+    fn write_amount(
+        &self,
+        f: &mut impl core::fmt::Write,
+        system: Option<UnitSystem>,
+    ) -> core::fmt::Result {
+        self.quantity.write_in(f, system)
+    }
+    fn to_normalized_string(&self, depth: usize) -> String {
+        let mut out = "  ".repeat(depth);
+        out.push_str("- ");
+        // Writing to a String can't fail
+        let _ = self.write_amount(&mut out, None);
+        out.push_str(&self.name.split_whitespace().collect::<Vec<_>>().join(" "));
+        if let Some(preparation) = &self.preparation {
+            out.push_str(", ");
+            out.push_str(preparation.trim());
+        }
+        if let Quantity::ToTaste { .. } = self.quantity {
+            out.push_str(", to taste");
+        }
+        if let Some(note) = &self.note {
+            out.push_str(&format!(" ({})", note.trim()));
+        }
+        if self.optional {
+            out.push_str(" (optional)");
+        }
+        if self.fixed {
+            out.push_str(" {fixed}");
+        }
+        out
+    }
+    pub fn as_volume(&self) -> Option<&Volume> {
+        match &self.quantity {
+            Quantity::Volume(volume) => Some(volume),
+            _ => None,
+        }
+    }
+    pub fn as_simple(&self) -> Option<f32> {
+        match self.quantity {
+            Quantity::Simple(count) => Some(count),
+            _ => None,
+        }
+    }
+    pub fn depth(&self) -> usize {
+        // Nested bullets are indented past their parent's '- '
+        self.indent_width() / 2
+    }
+    // How far the bullet is indented, counting a tab as four spaces
+    fn indent_width(&self) -> usize {
+        let indent = self.indent.rsplit('\n').next().unwrap_or_default();
+        indent.chars().map(|c| if c == '\t' { 4 } else { 1 }).sum()
+    }
+    /// Where a markdown link in the name points, like the `dough.md` in
+    /// `1 batch [pizza dough](dough.md)`, for ingredients that are
+    /// recipes of their own.
+    pub fn link(&self) -> Option<&str> {
+        find_link(&self.name).map(|(_, _, target)| target)
+    }
+    /// The name with any markdown link replaced by its text.
+    pub fn plain_name(&self) -> Cow<'_, str> {
+        match find_link(&self.name) {
+            Some((range, text, _)) => format!(
+                "{}{text}{}",
+                &self.name[..range.start],
+                &self.name[range.end..]
+            )
+            .into(),
+            None => Cow::Borrowed(&self.name),
+        }
+    }
+    pub fn pluralize(&self) -> Self {
+        Pluralizer::default().apply(self)
+    }
+    /// The same ingredient with another quantity, keeping its indent,
+    /// name, notes and markers.
+    pub fn with_quantity(self, quantity: Quantity) -> Self {
+        Self {
+            quantity,
+            amount_text: None,
+            ..self
+        }
+    }
+    /// The same ingredient under another name, keeping its quantity,
+    /// notes and markers.
+    pub fn with_name(self, name: impl Into<Cow<'a, str>>) -> Self {
+        Self {
+            name: name.into(),
+            ..self
+        }
+    }
+    fn scale(&self, factor: f32) -> Self {
+        let quantity = match self.fixed {
+            true => self.quantity.clone(),
+            false => self.quantity.scale(factor),
+        };
+        Self {
+            indent: self.indent.clone(),
+            bullet: self.bullet,
+            bullet_spacing: self.bullet_spacing.clone(),
+            quantity,
+            amount_text: self.amount_text.clone(),
+            divider: self.divider.clone(),
+            name_separator: self.name_separator.clone(),
+            name: self.name.clone(),
+            preparation: self.preparation.clone(),
+            note: self.note.clone(),
+            optional: self.optional,
+            optional_marker: self.optional_marker.clone(),
+            fixed: self.fixed,
+            fixed_marker: self.fixed_marker.clone(),
+            trailing: self.trailing.clone(),
+        }
+    }
+    /// Parses a single bulleted ingredient line, such as `- 2 cups flour`.
+    pub fn try_parse(src: &'a str) -> Result<Self, ParseError> {
+        Self::parse_with(src, &ParseOptions::default())
+    }
+    fn parse_with(src: &'a str, options: &ParseOptions) -> Result<Self, ParseError> {
+        let Some((indent, bullet, spacing, tail)) = split_bullet(src) else {
+            let line = src.lines().next().unwrap_or_default();
+            return Err(ParseError::MissingBullet {
+                line: line.to_string(),
+                line_number: 1,
+            });
+        };
+        // Keep the line ending (and anything else after the name) apart,
+        // including the closing border of a table row
+        let mut name_end = tail.trim_end().len();
+        if bullet == '|' {
+            name_end = tail[..name_end].trim_end_matches('|').trim_end().len();
+        }
+        let (tail, trailing) = tail.split_at(name_end);
+        // Ingredients that shouldn't be scaled end with '{fixed}'
+        let mut fixed_marker = None;
+        if let Some(name) = tail.strip_suffix("{fixed}") {
+            fixed_marker = Some(&tail[name.trim_end().len()..]);
+        }
+        let tail = &tail[..tail.len() - fixed_marker.map_or(0, str::len)];
+        // Optional ingredients might be marked with a leading 'optional:'
+        let mut optional_marker = None;
+        if tail.to_lowercase().starts_with("optional:") {
+            let amount_start = tail.len() - tail["optional:".len()..].trim_start().len();
+            optional_marker = Some(&tail[..amount_start]);
+        }
+        let tail = &tail[optional_marker.map_or(0, str::len)..];
+        // Ingredients might be written name first, like 'Flour: 2 cups'
+        let name_first = options
+            .name_first
+            .then(|| split_name_first(tail, options))
+            .flatten();
+        let (quantity, name, amount_text, name_separator) = match name_first {
+            Some((name, separator, quantity, amount_text)) => {
+                (quantity, name, amount_text, Some(separator))
+            }
+            None => {
+                let (quantity, name, amount_text) = parse_quantity(tail, options);
+                (quantity, name, amount_text, None)
+            }
+        };
+        // Table rows have another border between the amount and the name
+        let (divider, name) = match name.find('|') {
+            Some(border) if bullet == '|' => {
+                let name_start = name.len() - name[border + 1..].trim_start().len();
+                let (divider, name) = name.split_at(name_start);
+                (Some(divider), name)
+            }
+            _ => (None, name),
+        };
+        let full_name = name;
+        let (mut name, mut note) = split_note(full_name);
+        // Or with a trailing '(optional)', which may follow another note
+        if optional_marker.is_none()
+            && note.is_some_and(|note| note.eq_ignore_ascii_case("optional"))
+        {
+            optional_marker = Some(&full_name[name.len()..]);
+            (name, note) = split_note(name);
+        }
+        // Unmeasured ingredients might be seasoned to taste
+        let (quantity, name) = match (quantity, split_to_taste(name)) {
+            (Quantity::None, Some((name, comma))) => (Quantity::ToTaste { comma }, name),
+            (quantity, _) => (quantity, name),
+        };
+        // And might say how they're prepared, like '2 apples, diced'
+        let (name, preparation) = match name.split_once(", ") {
+            Some((name, preparation)) if options.preparation && !name.is_empty() => {
+                (name, Some(preparation))
+            }
+            _ => (name, None),
+        };
+        Ok(Self {
+            indent: indent.into(),
+            bullet,
+            bullet_spacing: Some(spacing).filter(|s| *s != " ").map(Into::into),
+            quantity,
+            amount_text: amount_text.map(Into::into),
+            divider: divider.map(Into::into),
+            name_separator: name_separator.map(Into::into),
+            name: name.into(),
+            preparation: preparation.map(Into::into),
+            note: note.map(Into::into),
+            optional: optional_marker.is_some(),
+            optional_marker: optional_marker.map(Into::into),
+            fixed: fixed_marker.is_some(),
+            fixed_marker: fixed_marker.map(Into::into),
+            trailing: trailing.into(),
+        })
+    }
+}
+
+// Parse a block of ingredients, either as a list or a table
+fn parse_ingredients<'a>(
+    src: &'a str,
+    options: &ParseOptions,
+    recover: bool,
+) -> Result<Vec<Ingredient<'a>>, ParseError> {
+    if src.starts_with('|') {
+        return parse_table(src, options, recover);
+    }
+    parse_items(src, IngredientLines::new(src), options, recover)
+}
+
+// Parse each item of a block, either failing on the first malformed one
+// or keeping its text at the end of the ingredient before it (or the
+// start of the one after, if it comes first)
+fn parse_items<'a>(
+    src: &'a str,
+    items: impl Iterator<Item = &'a str>,
+    options: &ParseOptions,
+    recover: bool,
+) -> Result<Vec<Ingredient<'a>>, ParseError> {
+    let mut ingredients: Vec<Ingredient<'a>> = vec![];
+    let mut skipped = String::new();
+    for item in items {
+        match Ingredient::parse_with(item, options) {
+            Ok(mut ingredient) => {
+                if !skipped.is_empty() {
+                    skipped.push_str(&ingredient.indent);
+                    ingredient.indent = core::mem::take(&mut skipped).into();
+                }
+                ingredients.push(ingredient);
+            }
+            Err(_) if recover => match ingredients.last_mut() {
+                Some(last) => last.trailing = format!("{}{item}", last.trailing).into(),
+                None => skipped.push_str(item),
+            },
+            Err(e) => return Err(e.after(src, item)),
+        }
+    }
+    Ok(ingredients)
+}
+
+// Parse a markdown table of ingredients with amount and name columns,
+// keeping its header and divider rows in front of the first ingredient
+fn parse_table<'a>(
+    src: &'a str,
+    options: &ParseOptions,
+    recover: bool,
+) -> Result<Vec<Ingredient<'a>>, ParseError> {
+    let header_len: usize = src.split_inclusive('\n').take(2).map(str::len).sum();
+    // Blank lines are kept with the row before them
+    let mut rows: Vec<(usize, usize)> = vec![];
+    let mut offset = header_len;
+    for line in src[header_len..].split_inclusive('\n') {
+        let end = offset + line.len();
+        match rows.last_mut() {
+            Some((_, row_end)) if line.trim().is_empty() => *row_end = end,
+            _ => rows.push((offset, end)),
+        }
+        offset = end;
+    }
+    let rows = rows.into_iter().map(|(start, end)| &src[start..end]);
+    let mut ingredients = parse_items(src, rows, options, recover)?;
+    if let Some(first) = ingredients.first_mut() {
+        first.indent = src[..header_len + first.indent.len()].into();
+    }
+    Ok(ingredients)
+}
+
+// Split the first markdown list item in a block into the text before
+// its bullet, the bullet itself, the spaces after it and the rest of the
+// item. Bullets start their line, so any lines of prose before them are
+// kept in front.
+fn split_bullet(src: &str) -> Option<(&str, char, &str, &str)> {
+    let mut offset = 0;
+    for line in src.split_inclusive('\n') {
+        let item = line.trim_start_matches([' ', '\t']);
+        let start = offset + line.len() - item.len();
+        offset += line.len();
+        if let Some(bullet) = leading_bullet(line) {
+            let rest = &src[start + 1..];
+            let spacing = &rest[..rest.len() - rest.trim_start_matches([' ', '\t']).len()];
+            return Some((&src[..start], bullet, spacing, &rest[spacing.len()..]));
+        }
+    }
+    None
+}
+
+// The bullet starting a markdown list item, which must be followed by a
+// space so rules like '---' and amounts like '-2' aren't taken for one
+fn leading_bullet(line: &str) -> Option<char> {
+    let mut chars = line.trim_start_matches([' ', '\t']).chars();
+    match (chars.next(), chars.next()) {
+        (Some(bullet @ ('-' | '*' | '+' | '|')), Some(' ')) => Some(bullet),
+        _ => None,
+    }
+}
+
+// How deeply each ingredient in a list is nested, with each one a
+// level below the closest ingredient above it that's indented less
+fn nesting_depths(ingredients: &[Ingredient]) -> Vec<usize> {
+    let mut parents: Vec<usize> = vec![];
+    ingredients
+        .iter()
+        .map(|ingredient| {
+            let width = ingredient.indent_width();
+            while parents.last().is_some_and(|&parent| parent >= width) {
+                parents.pop();
+            }
+            let depth = parents.len();
+            parents.push(width);
+            depth
+        })
+        .collect()
+}
+
+#[derive(Debug, Clone)]
+pub struct Pluralizer {
+    irregular: Vec<(String, String)>,
+}
+
+impl Default for Pluralizer {
+    fn default() -> Self {
+        let irregular = [
+            ("leaf", "leaves"),
+            ("loaf", "loaves"),
+            ("half", "halves"),
+            ("potato", "potatoes"),
+            ("tomato", "tomatoes"),
+            ("berry", "berries"),
+            ("cherry", "cherries"),
+            ("anchovy", "anchovies"),
+            ("dash", "dashes"),
+            ("pinch", "pinches"),
+            ("radish", "radishes"),
+        ];
+        Pluralizer {
+            irregular: irregular
+                .into_iter()
+                .map(|(singular, plural)| (singular.to_string(), plural.to_string()))
+                .collect(),
+        }
+    }
+}
+
+impl Pluralizer {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    pub fn irregular(mut self, singular: &str, plural: &str) -> Self {
+        let (singular, plural) = (singular.to_lowercase(), plural.to_lowercase());
+        self.irregular.retain(|(s, _)| *s != singular);
+        self.irregular.push((singular, plural));
+        self
+    }
+    pub fn singular(&self, word: &str) -> String {
+        let lowercase = word.to_lowercase();
+        if let Some((singular, _)) = self.irregular.iter().find(|(_, p)| *p == lowercase) {
+            return singular.clone();
+        }
+        match word.strip_suffix('s') {
+            Some(singular) if !singular.ends_with('s') && !singular.is_empty() => {
+                singular.to_string()
+            }
+            _ => word.to_string(),
+        }
+    }
+    pub fn plural(&self, word: &str) -> String {
+        let lowercase = word.to_lowercase();
+        if let Some((_, plural)) = self.irregular.iter().find(|(s, _)| *s == lowercase) {
+            return plural.clone();
+        }
+        if self.irregular.iter().any(|(_, p)| *p == lowercase) || word.ends_with('s') {
+            return word.to_string();
+        }
+        format!("{word}s")
+    }
+    /// Matches the noun in a counted ingredient's name to its count, so
+    /// '2 egg' becomes '2 eggs' and '1 cloves of garlic' becomes
+    /// '1 clove of garlic'. The noun is taken to be the word before
+    /// ' of ', or else the last word of the name.
+    pub fn apply<'a>(&self, ingredient: &Ingredient<'a>) -> Ingredient<'a> {
+        let mut ingredient = ingredient.clone();
+        let count = match ingredient.quantity {
+            Quantity::Simple(count) | Quantity::Fixed(count) => count,
+            Quantity::Range { high, .. } => high,
+            _ => return ingredient,
+        };
+        let name = &ingredient.name;
+        let core::ops::Range {
+            start: noun_start,
+            end: noun_end,
+        } = noun_range(name);
+        let noun = &name[noun_start..noun_end];
+        let noun = if count > 0.0 && count <= 1.0 {
+            self.singular(noun)
+        } else {
+            self.plural(noun)
+        };
+        let name = format!("{}{noun}{}", &name[..noun_start], &name[noun_end..]);
+        ingredient.name = name.into();
+        ingredient
+    }
+}
+
+// Find the noun an ingredient's name counts, which is its last word or the
+// word before ' of', like 'slices' in 'slices of bread'
+fn noun_range(name: &str) -> core::ops::Range<usize> {
+    let noun_end = name.find(" of ").unwrap_or(name.len());
+    let noun_start = name[..noun_end].rfind(' ').map_or(0, |i| i + 1);
+    noun_start..noun_end
+}
+
+pub struct IngredientLines<'a>(&'a str);
+
+impl<'a> IngredientLines<'a> {
+    pub fn new(src: &'a str) -> Self {
+        IngredientLines(src)
+    }
+}
+
+impl<'a> Iterator for IngredientLines<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        // Store the current tail
+        let src = self.0;
+        // Skip past the start of the md item, handing back any
+        // leftover text so the parser can report it
+        let Some((_, _, _, tail)) = split_bullet(src) else {
+            self.0 = "";
+            return (!src.trim().is_empty()).then_some(src);
+        };
+        // Find the start of the next item, which can't be on the
+        // same line as this one's bullet
+        for line in tail.split("\n").skip(1) {
+            if leading_bullet(line).is_some() {
                 let end = line.as_ptr() as usize;
                 let len = end - src.as_ptr() as usize;
                 let (next, src) = src.split_at(len);
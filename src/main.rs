@@ -0,0 +1,95 @@
+// SPDX-FileCopyrightText: 2025 Madeline Baggins <madeline@baggins.family>
+//
+// SPDX-License-Identifier: GPL-3.0-only
+
+//! Scales a markdown recipe and prints it to stdout.
+
+use std::process::ExitCode;
+
+use maddi_recipe::Recipe;
+
+const USAGE: &str = "usage: maddi-recipe <file> [--scale <factor> | --servings <count>]";
+
+enum Scaling {
+    Factor(f32),
+    Servings(u32),
+}
+
+struct Args {
+    path: String,
+    scaling: Option<Scaling>,
+}
+
+fn main() -> ExitCode {
+    let args = match parse_args(std::env::args().skip(1)) {
+        Ok(Some(args)) => args,
+        Ok(None) => {
+            println!("{USAGE}");
+            return ExitCode::SUCCESS;
+        }
+        Err(err) => {
+            eprintln!("maddi-recipe: {err}\n{USAGE}");
+            return ExitCode::FAILURE;
+        }
+    };
+    match run(args) {
+        Ok(recipe) => {
+            print!("{recipe}");
+            ExitCode::SUCCESS
+        }
+        Err(err) => {
+            eprintln!("maddi-recipe: {err}");
+            ExitCode::FAILURE
+        }
+    }
+}
+
+// Returns no arguments when help was asked for
+fn parse_args(mut args: impl Iterator<Item = String>) -> Result<Option<Args>, String> {
+    let mut path = None;
+    let mut scaling = None;
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(None),
+            "--scale" | "--servings" if scaling.is_some() => {
+                return Err("only one of --scale and --servings can be given".to_string());
+            }
+            "--scale" => {
+                let value = args.next().ok_or("--scale needs a factor")?;
+                match value.parse::<f32>() {
+                    Ok(factor) if factor.is_finite() && factor > 0.0 => {
+                        scaling = Some(Scaling::Factor(factor))
+                    }
+                    _ => return Err(format!("invalid scale factor '{value}'")),
+                }
+            }
+            "--servings" => {
+                let value = args.next().ok_or("--servings needs a count")?;
+                match value.parse::<u32>() {
+                    Ok(count) if count > 0 => scaling = Some(Scaling::Servings(count)),
+                    _ => return Err(format!("invalid number of servings '{value}'")),
+                }
+            }
+            option if option.starts_with('-') => {
+                return Err(format!("unknown option '{option}'"));
+            }
+            _ if path.is_some() => return Err(format!("unexpected argument '{arg}'")),
+            _ => path = Some(arg),
+        }
+    }
+    let path = path.ok_or("no recipe file given")?;
+    Ok(Some(Args { path, scaling }))
+}
+
+fn run(args: Args) -> Result<Recipe<'static>, String> {
+    let src = std::fs::read_to_string(&args.path).map_err(|e| format!("{}: {e}", args.path))?;
+    let recipe = Recipe::try_parse(&src).map_err(|e| format!("{}: {e}", args.path))?;
+    let scaled = match args.scaling {
+        None => recipe,
+        Some(Scaling::Factor(factor)) => recipe.scale(factor),
+        Some(Scaling::Servings(count)) => recipe
+            .scale_to(count)
+            .ok_or_else(|| format!("{}: the recipe doesn't say how many it serves", args.path))?,
+    };
+    Ok(scaled.into_static())
+}
@@ -2,7 +2,14 @@
 //
 // SPDX-License-Identifier: GPL-3.0-only
 
-use crate::Recipe;
+use std::time::Duration;
+
+use crate::{
+    AmountError, CategoryTable, Density, DensityTable, Difficulty, Ingredient, IngredientChange,
+    IngredientLines, Lint, LintKind, ParseError, ParseOptions, Pluralizer, Quantity,
+    QuantitySummary, Recipe, RecipeBuilder, ScaleOptions, SortKey, TaskStep, Temperature,
+    TemperatureUnit, UnitLabels, UnitSystem, Volume, VolumeFormatter, parse_amount,
+};
 
 #[test]
 fn pizza() {
@@ -12,3 +19,2010 @@ fn pizza() {
     println!("{scaled}");
     assert_eq!(pizza_src, format!("{recipe}"));
 }
+
+#[test]
+fn bread() {
+    let bread_src = include_str!("bread.md");
+    let recipe = Recipe::parse(bread_src);
+    assert!(matches!(
+        recipe.ingredients[0].quantity,
+        Quantity::Weight(_)
+    ));
+    let scaled = recipe.scale(0.5);
+    println!("{scaled}");
+    let Quantity::Weight(flour) = &scaled.ingredients[0].quantity else {
+        panic!("Expected the flour to be weighed.");
+    };
+    assert_eq!(flour.grams(), 250.0);
+    assert_eq!(bread_src, format!("{recipe}"));
+}
+
+#[test]
+fn metric_volume() {
+    let recipe = Recipe::parse("# Milk\n\n## Ingredients\n\n- 250 ml milk\n");
+    let Quantity::Volume(milk) = &recipe.ingredients[0].quantity else {
+        panic!("Expected the milk to be a volume.");
+    };
+    let milliliters = |qtr_tsps: f32| qtr_tsps / 4.0 * 4.92892;
+    assert!((milliliters(milk.quarter_teaspoons()) - 250.0).abs() < 0.01);
+    let doubled = milk.scale(2.0);
+    assert!((milliliters(doubled.quarter_teaspoons()) - 500.0).abs() < 0.01);
+    // Metric volumes are written back in metric, scaled or not
+    let src = "# Soup\n\n## Ingredients\n\n- 250 ml milk\n- 1 l stock\n- 1 cup rice\n";
+    let recipe = Recipe::parse(src);
+    assert_eq!(recipe.to_string(), src);
+    assert_eq!(
+        recipe.scale(2.0).to_string(),
+        "# Soup\n\n## Ingredients\n\n- 500 ml milk\n- 2 l stock\n- 2 cups rice\n"
+    );
+    assert_eq!(
+        recipe.display_in(UnitSystem::Imperial).to_string(),
+        "# Soup\n\n## Ingredients\n\n- 1 cup + 2 + 1/2 + 1/4 tsps milk\n\
+        - 4 cups + 3 tbsps + 1 + 1/2 + 1/4 + 1/8 tsps stock\n- 1 cup rice\n"
+    );
+}
+
+#[test]
+fn metric_display() {
+    let recipe = Recipe::parse("# Stock\n\n## Ingredients\n\n- 2 cups stock\n- 6 cups water\n");
+    let metric: Vec<String> = recipe
+        .ingredients
+        .iter()
+        .map(|i| match &i.quantity {
+            Quantity::Volume(volume) => volume.to_metric_string(),
+            _ => panic!("Expected a volume."),
+        })
+        .collect();
+    assert_eq!(metric, ["473 ml", "1.4 l"]);
+}
+
+#[test]
+fn malformed_ingredient() {
+    let src = "# Oops\n\n## Ingredients\n\n1 cup flour\nnot an ingredient\n";
+    let error = Recipe::try_parse(src).unwrap_err();
+    assert_eq!(
+        error,
+        ParseError::MissingBullet {
+            line: "1 cup flour".to_string(),
+            line_number: 5,
+        }
+    );
+    assert_eq!(error.line_number(), 5);
+    assert_eq!(
+        error.to_string(),
+        "line 5: expected an ingredient bullet, found '1 cup flour'"
+    );
+    // Line numbers count from the start of the recipe, not the table
+    let src = "# Oops\n\n## Ingredients\n\n| Amount | Name |\n| --- | --- |\n\
+        | 1 cup | flour |\nsome sugar\n";
+    assert_eq!(Recipe::try_parse(src).unwrap_err().line_number(), 8);
+    // The rest of the recipe is still read, keeping the malformed lines
+    let recipe = Recipe::parse(src);
+    assert_eq!(recipe.ingredients.len(), 1);
+    assert_eq!(recipe.ingredients[0].trailing, " |\nsome sugar\n");
+    assert_eq!(recipe.to_string(), src);
+    let src = "# Oops\n\n## Ingredients\n\nNone needed!\n\n\
+        ## For the Filling\n\n- 2 apples\n\n## Instructions\n\nBake.\n";
+    assert!(Recipe::try_parse(src).is_err());
+    let recipe = Recipe::parse(src);
+    assert_eq!(recipe.to_string(), src);
+    assert_eq!(
+        recipe.scale(2.0).to_string(),
+        src.replace("2 apples", "4 apples")
+    );
+    assert_eq!(recipe.instructions, "\n## Instructions\n\nBake.\n");
+    let src = "# Oops\n\n## Ingredients\n\n| Amount | Name |\n| --- | --- |\n\
+        None needed!\n| 2 | eggs |\n| 1 cup | flour |\n";
+    let recipe = Recipe::parse(src);
+    assert_eq!(recipe.to_string(), src);
+    assert_eq!(
+        recipe.scale(2.0).to_string(),
+        src.replace("| 2 | eggs", "| 4 | eggs")
+            .replace("1 cup", "2 cups")
+    );
+    // Without a bullet after it, a line stays part of the item before it
+    let src = "# Oops\n\n## Ingredients\n\n- 1 cup flour\n-not an ingredient\n";
+    let recipe = Recipe::try_parse(src).unwrap();
+    assert_eq!(recipe.ingredients[0].name, "flour\n-not an ingredient");
+    assert_eq!(recipe.to_string(), src);
+}
+
+#[test]
+fn unicode_fractions() {
+    assert_eq!(parse_amount("½"), Ok(0.5));
+    assert_eq!(parse_amount("1½"), Ok(1.5));
+    assert_eq!(parse_amount("NaN"), Err(AmountError));
+    assert_eq!(parse_amount("1/0"), Err(AmountError));
+    assert_eq!(parse_amount("½ cup"), Err(AmountError));
+    let recipe = Recipe::parse("# Cake\n\n## Ingredients\n\n- 2 ⅓ cups flour\n- ½ lemon\n");
+    let Quantity::Volume(flour) = &recipe.ingredients[0].quantity else {
+        panic!("Expected the flour to be a volume.");
+    };
+    assert!((flour.quarter_teaspoons() - 7.0 / 3.0 * 192.0).abs() < 0.001);
+    assert_eq!(recipe.ingredients[0].name, "flour");
+    assert!(matches!(
+        recipe.ingredients[1].quantity,
+        Quantity::Simple(0.5)
+    ));
+}
+
+#[test]
+fn ranges() {
+    let src = "# Soup\n\n## Ingredients\n\n- 2-3 cloves garlic\n- 1–2 cups broth\n";
+    let recipe = Recipe::parse(src);
+    assert_eq!(src, format!("{recipe}"));
+    let Quantity::Range {
+        low, high, unit, ..
+    } = &recipe.ingredients[0].quantity
+    else {
+        panic!("Expected the garlic to be a range.");
+    };
+    assert_eq!((*low, *high, unit.as_deref()), (2.0, 3.0, None));
+    assert_eq!(recipe.ingredients[1].name, "broth");
+    let scaled = recipe.scale(2.0);
+    assert_eq!(
+        format!("{scaled}"),
+        "# Soup\n\n## Ingredients\n\n- 4-6 cloves garlic\n- 2–4 cups broth\n"
+    );
+    // Spaces around the dash are kept
+    let src = "# Soup\n\n## Ingredients\n\n- 2 - 3 cups broth\n- 1 –2 carrots\n- 3 - 4 onions\n";
+    let recipe = Recipe::parse(src);
+    assert_eq!(recipe.to_string(), src);
+    let Quantity::Range {
+        low,
+        high,
+        separator,
+        unit,
+    } = &recipe.ingredients[0].quantity
+    else {
+        panic!("Expected the broth to be a range.");
+    };
+    assert_eq!((*low, *high), (2.0, 3.0));
+    assert_eq!((separator.as_str(), unit.as_deref()), (" - ", Some("cups")));
+    assert_eq!(recipe.ingredients[0].name, "broth");
+    assert_eq!(recipe.ingredients[2].name, "onions");
+    assert_eq!(
+        recipe.scale(2.0).to_string(),
+        "# Soup\n\n## Ingredients\n\n- 4 - 6 cups broth\n- 2 –4 carrots\n- 6 - 8 onions\n"
+    );
+}
+
+#[test]
+fn equality() {
+    let pizza_src = include_str!("pizza.md");
+    let recipe = Recipe::parse(pizza_src);
+    assert_eq!(recipe, Recipe::parse(pizza_src));
+    assert_eq!(recipe, recipe.scale(3.0).scale(1.0 / 3.0));
+    assert_ne!(recipe, recipe.scale(2.0));
+    let tablespoon = Recipe::parse("# A\n\n## Ingredients\n\n- 1 tbsp oil\n");
+    let teaspoons = Recipe::parse("# A\n\n## Ingredients\n\n- 3 tsps oil\n");
+    assert_eq!(
+        tablespoon.ingredients[0].quantity,
+        teaspoons.ingredients[0].quantity
+    );
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn serde_round_trip() {
+    let pizza_src = include_str!("pizza.md");
+    let recipe = Recipe::parse(pizza_src);
+    let json = serde_json::to_string(&recipe).unwrap();
+    let deserialized: Recipe = serde_json::from_str(&json).unwrap();
+    assert_eq!(pizza_src, format!("{deserialized}"));
+}
+
+#[test]
+fn volume_constructors() {
+    assert_eq!(Volume::from_cups(1.0), Volume::from_teaspoons(48.0));
+    assert_eq!(Volume::from_teaspoons(1.0).quarter_teaspoons(), 4.0);
+    assert_eq!(Volume::from_quarter_teaspoons(6.0).to_string(), "1/2 tbsp");
+    assert_eq!(Volume::from_milliliters(250.0).to_metric_string(), "250 ml");
+}
+
+#[test]
+fn calzone() {
+    let calzone_src = include_str!("calzone.md");
+    let recipe = Recipe::parse(calzone_src);
+    assert!(recipe.ingredients.is_empty());
+    let titles: Vec<&str> = recipe.sections.iter().map(|s| &*s.title).collect();
+    assert_eq!(
+        titles,
+        ["For the Dough", "For the Filling", "Optional extras"]
+    );
+    assert_eq!(recipe.sections[1].ingredients.len(), 2);
+    assert_eq!(recipe.all_ingredients().count(), 6);
+    assert!(recipe.instructions.starts_with("\n## Instructions"));
+    let scaled = recipe.scale(2.0);
+    assert_eq!(
+        scaled.sections[2].ingredients[0].quantity,
+        Quantity::Volume(Volume::from_cups(0.5))
+    );
+    assert_eq!(calzone_src, format!("{recipe}"));
+}
+
+#[test]
+fn heading_after_ingredients() {
+    // Headings without any ingredients under them aren't subsections
+    let src = "# Bread\n\n## Ingredients\n\n- 1 cup flour\n\n### Tips\n\nUse good flour.\n\n## Instructions\n\nBake.\n";
+    let recipe = Recipe::try_parse(src).unwrap();
+    assert!(recipe.sections.is_empty());
+    assert_eq!(recipe.ingredients.len(), 1);
+    assert!(
+        recipe
+            .instructions
+            .starts_with("\n### Tips\n\nUse good flour.")
+    );
+    assert_eq!(recipe.to_string(), src);
+    let src =
+        "# Bread\n\n## Ingredients\n\n- 1 cup flour\n\n### Notes\n\n## Instructions\n\nBake.\n";
+    let recipe = Recipe::try_parse(src).unwrap();
+    assert!(recipe.sections.is_empty());
+    assert_eq!(recipe.to_string(), src);
+}
+
+#[test]
+fn servings() {
+    let src = "# Rice\n\nServes 2\n\n## Ingredients\n\n- 1 cup rice\n";
+    let recipe = Recipe::parse(src);
+    assert_eq!(recipe.servings(), Some(2));
+    let scaled = recipe.scale_to(6).unwrap();
+    assert_eq!(
+        scaled.ingredients[0].quantity,
+        Quantity::Volume(Volume::from_cups(3.0))
+    );
+    assert_eq!(recipe.scale_to_servings(0, 4), recipe);
+    assert_eq!(Recipe::parse("# No servings\n").scale_to(4), None);
+}
+
+#[test]
+fn consolidate() {
+    let src =
+        "# Bread\n\n## Ingredients\n\n- 2 cups flour\n- 1 cup milk\n- 1 cup Flour\n- 2 milk\n";
+    let recipe = Recipe::parse(src).consolidate();
+    assert_eq!(
+        format!("{recipe}"),
+        "# Bread\n\n## Ingredients\n\n- 3 cups flour\n- 1 cup milk\n- 2 milk\n"
+    );
+}
+
+#[test]
+fn volume_arithmetic() {
+    let mut volume = Volume::from_cups(1.0) + Volume::from_cups(0.5);
+    assert_eq!(volume, Volume::from_cups(1.5));
+    volume += Volume::from_teaspoons(3.0);
+    assert_eq!(volume, Volume::from_teaspoons(75.0));
+    let volumes = [Volume::from_cups(1.0), Volume::from_teaspoons(48.0)];
+    assert_eq!(volumes.iter().sum::<Volume>(), Volume::from_cups(2.0));
+    assert_eq!(volumes.into_iter().sum::<Volume>(), Volume::from_cups(2.0));
+}
+
+#[test]
+fn recipe_yield() {
+    let src = "# Muffins\n\nYield: 12 muffins\n\n## Ingredients\n\n- 2 cups flour\n";
+    let recipe = Recipe::parse(src);
+    let recipe_yield = recipe.yield_.as_ref().unwrap();
+    assert_eq!(recipe_yield.count, 12);
+    assert_eq!(recipe_yield.unit.as_deref(), Some("muffins"));
+    assert_eq!(src, format!("{recipe}"));
+    let serves = Recipe::parse("# Soup\n\nServes 4\n");
+    let serves_yield = serves.yield_.unwrap();
+    assert_eq!((serves_yield.count, serves_yield.unit), (4, None));
+}
+
+#[test]
+fn decimal_display() {
+    let volume = Volume::from_cups(1.5);
+    assert_eq!(volume.to_decimal_cups(), 1.5);
+    assert_eq!(
+        volume.to_decimal_string("cups").as_deref(),
+        Some("1.5 cups")
+    );
+    let volume = Volume::from_quarter_teaspoons(1.0);
+    assert_eq!(volume.to_decimal_string("tsp").as_deref(), Some("0.25 tsp"));
+    assert_eq!(volume.to_decimal_string("bushels"), None);
+}
+
+#[test]
+fn small_teaspoons() {
+    let quarter = Volume::from_teaspoons(0.25);
+    assert_eq!(quarter.scale(0.5).to_string(), "1/8 tsp");
+    assert_eq!(quarter.scale(0.25).to_string(), "1/16 tsp");
+    assert_eq!(quarter.scale(0.1875).to_string(), "1/16 tsp");
+    assert_eq!(quarter.scale(0.75).to_string(), "1/8 + 1/16 tsps");
+}
+
+#[test]
+fn pinches() {
+    use crate::quarter_teaspoons::{DASH, PINCH, SMIDGEN};
+    let src =
+        "# Eggs\n\n## Ingredients\n\n- 2 pinches salt\n- a dash of hot sauce\n- 1 smidgen pepper\n";
+    let recipe = Recipe::parse(src);
+    let quantities: Vec<&Quantity> = recipe.ingredients.iter().map(|i| &i.quantity).collect();
+    assert_eq!(
+        quantities,
+        [
+            &Quantity::Volume(Volume::from_quarter_teaspoons(2.0 * PINCH)),
+            &Quantity::Volume(Volume::from_quarter_teaspoons(DASH)),
+            &Quantity::Volume(Volume::from_quarter_teaspoons(SMIDGEN)),
+        ]
+    );
+    assert_eq!(Volume::from_quarter_teaspoons(0.05).to_string(), "a pinch");
+    assert_eq!(
+        Volume::from_quarter_teaspoons(PINCH).to_string(),
+        "1/16 tsp"
+    );
+}
+
+#[test]
+fn ingredient_names() {
+    let recipe = Recipe::parse(include_str!("pizza.md"));
+    let names: Vec<&str> = recipe.ingredient_names().collect();
+    assert_eq!(
+        names,
+        [
+            "pizza base",
+            "veggies",
+            "of mushrooms",
+            "of diced capsicum",
+            "of pizza sauce",
+            "of vegan cheese",
+            "of pineapple",
+            "of mixed herbs",
+            "of something else",
+            "of another",
+        ]
+    );
+}
+
+#[test]
+fn trailing_whitespace() {
+    let pizza_src = include_str!("pizza.md");
+    let recipe = Recipe::parse(pizza_src);
+    let last = recipe.ingredients.last().unwrap();
+    assert_eq!(last.name, "of another");
+    assert_eq!(last.trailing, "\n");
+    assert_eq!(pizza_src, format!("{recipe}"));
+}
+
+#[test]
+#[cfg(feature = "std")]
+fn from_reader() {
+    let pizza_src = include_str!("pizza.md");
+    let recipe = Recipe::from_reader(pizza_src.as_bytes()).unwrap();
+    assert_eq!(recipe, Recipe::parse(pizza_src));
+    let malformed = "# Oops\n\n## Ingredients\n\n-oops\n".as_bytes();
+    let error = Recipe::from_reader(malformed).unwrap_err();
+    assert_eq!(error.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn large_volumes() {
+    let src = "# Punch\n\n## Ingredients\n\n- 1 pint cream\n- 4 fl oz rum\n- 2 qt juice\n- 1 gallon water\n";
+    let recipe = Recipe::parse(src);
+    let quantities: Vec<&Quantity> = recipe.ingredients.iter().map(|i| &i.quantity).collect();
+    assert_eq!(
+        quantities,
+        [
+            &Quantity::Volume(Volume::from_cups(2.0)),
+            &Quantity::Volume(Volume::from_cups(0.5)),
+            &Quantity::Volume(Volume::from_cups(8.0)),
+            &Quantity::Volume(Volume::from_cups(16.0)),
+        ]
+    );
+    assert_eq!(recipe.ingredients[1].name, "rum");
+    assert_eq!(
+        recipe.scale(2.0).ingredients[0].to_string(),
+        "- 4 cups cream\n"
+    );
+}
+
+#[test]
+fn large_unit_display() {
+    let volume = Volume::from_cups(40.0);
+    assert_eq!(volume.to_string_large_units(), "2 gallons + 2 quarts");
+    let volume = Volume::from_cups(21.0);
+    assert_eq!(volume.to_string_large_units(), "1 gallon + 1 quart + 1 cup");
+    let volume = Volume::from_cups(0.5);
+    assert_eq!(volume.to_string_large_units(), "1/2 cup");
+}
+
+#[test]
+fn quantity_scale() {
+    assert_eq!(Quantity::None.scale(2.0), Quantity::None);
+    assert_eq!(Quantity::Simple(1.5).scale(2.0), Quantity::Simple(3.0));
+    assert_eq!(
+        Quantity::Volume(Volume::from_cups(1.0)).scale(0.5),
+        Quantity::Volume(Volume::from_cups(0.5))
+    );
+    let flour = Recipe::parse("# A\n\n## Ingredients\n\n- 200 g flour\n").ingredients[0]
+        .quantity
+        .clone();
+    let Quantity::Weight(weight) = flour.scale(3.0) else {
+        panic!("Expected a weight.");
+    };
+    assert_eq!(weight.grams(), 600.0);
+    let range = Quantity::Range {
+        low: 1.0,
+        high: 2.0,
+        separator: "-".to_string(),
+        unit: Some("cups".to_string()),
+    };
+    assert_eq!(
+        range.scale(2.0),
+        Quantity::Range {
+            low: 2.0,
+            high: 4.0,
+            separator: "-".to_string(),
+            unit: Some("cups".to_string()),
+        }
+    );
+}
+
+#[test]
+fn ingredient_lines() {
+    let block = "- 1 cup flour\n- veggies\n  - 1 carrot\n";
+    let lines: Vec<&str> = IngredientLines::new(block).collect();
+    assert_eq!(lines, ["- 1 cup flour\n", "- veggies\n", "  - 1 carrot\n"]);
+}
+
+#[test]
+fn nested_ingredients() {
+    let src = "# Salad\n\n## Ingredients\n\n- veggies\n  - peppers\n    - 1 red pepper\n    - 2 green peppers\n  - 1/2 cup of mushrooms\n- - for garnish\n  - 1 tbsp of parsley\n";
+    let recipe = Recipe::parse(src);
+    let depths: Vec<usize> = recipe.ingredients.iter().map(|i| i.depth()).collect();
+    assert_eq!(depths, [0, 1, 2, 2, 1, 0, 1]);
+    let names: Vec<&str> = recipe.ingredient_names().collect();
+    assert_eq!(
+        names,
+        [
+            "veggies",
+            "peppers",
+            "red pepper",
+            "green peppers",
+            "of mushrooms",
+            "- for garnish",
+            "of parsley"
+        ]
+    );
+    let scaled = recipe.scale(2.0);
+    assert_eq!(scaled.ingredients[3].quantity, Quantity::Simple(4.0));
+    assert_eq!(src, format!("{recipe}"));
+}
+
+#[test]
+fn volume_formatter() {
+    let third = Volume::from_cups(1.0 / 3.0);
+    assert_eq!(VolumeFormatter::new().format(&third), third.to_string());
+    assert_eq!(third.to_string(), "1/3 cup");
+    let eighths = VolumeFormatter::new().denominators([2, 4, 8]);
+    assert_eq!(eighths.format(&third), "1/4 cup + 1 tbsp + 1 tsp");
+    assert_eq!(eighths.format(&Volume::from_cups(0.375)), "3/8 cup");
+    let five_twelfths = Volume::from_cups(5.0 / 12.0);
+    assert_eq!(five_twelfths.to_string(), "1/3 cup + 1 tbsp + 1 tsp");
+    let quarters = VolumeFormatter::new().prefer_thirds(false);
+    assert_eq!(
+        quarters.format(&five_twelfths),
+        "1/4 cup + 2 tbsps + 2 tsps"
+    );
+}
+
+#[test]
+fn notes() {
+    let src = "# Bread\n\n## Ingredients\n\n- 2 cups flour (preferably bread flour)\n- salt (to (taste))\n";
+    let recipe = Recipe::parse(src);
+    let flour = &recipe.ingredients[0];
+    assert_eq!(flour.name, "flour");
+    assert_eq!(flour.note.as_deref(), Some("preferably bread flour"));
+    assert_eq!(recipe.ingredients[1].note, None);
+    assert_eq!(src, format!("{recipe}"));
+}
+
+#[test]
+fn to_taste() {
+    let src = "# Soup\n\n## Ingredients\n\n- salt to taste\n- pepper, to taste\n- 1 tsp chilli\n";
+    let recipe = Recipe::parse(src);
+    assert_eq!(recipe.ingredients[0].name, "salt");
+    assert_eq!(
+        recipe.ingredients[0].quantity,
+        Quantity::ToTaste { comma: false }
+    );
+    assert_eq!(recipe.ingredients[1].name, "pepper");
+    assert_eq!(
+        recipe.ingredients[1].quantity,
+        Quantity::ToTaste { comma: true }
+    );
+    assert_eq!(src, format!("{}", recipe.scale(1.0)));
+    assert_eq!(
+        recipe.scale(2.0).ingredients[1].quantity,
+        Quantity::ToTaste { comma: true }
+    );
+}
+
+#[test]
+fn quantity_summary() {
+    let recipe = Recipe::parse(include_str!("pizza.md"));
+    assert_eq!(recipe.ingredient_count(), 10);
+    assert_eq!(
+        recipe.quantity_summary(),
+        QuantitySummary {
+            none: 1,
+            simple: 1,
+            volume: 8,
+            ..Default::default()
+        }
+    );
+}
+
+#[test]
+fn title() {
+    let pizza_src = include_str!("pizza.md");
+    let recipe = Recipe::parse(pizza_src);
+    assert_eq!(recipe.title.as_deref(), Some("A fake recipe"));
+    assert_eq!(pizza_src, format!("{recipe}"));
+    assert_eq!(Recipe::parse("No title here\n# Late heading\n").title, None);
+}
+
+#[test]
+fn stars() {
+    let stars_src = include_str!("stars.md");
+    let recipe = Recipe::parse(stars_src);
+    let bullets: Vec<char> = recipe.ingredients.iter().map(|i| i.bullet).collect();
+    assert_eq!(bullets, ['*', '*', '+', '+', '*']);
+    assert_eq!(
+        recipe.ingredients[2].quantity,
+        Quantity::Volume(Volume::from_teaspoons(6.0))
+    );
+    assert_eq!(stars_src, format!("{recipe}"));
+}
+
+#[test]
+fn steps() {
+    let src = "# Toast\n\n## Ingredients\n\n- 1 slice of bread\n\n## Instructions\n\n1. Put the bread\n   in the toaster\n2. Wait\n\n10) Butter it\n";
+    let recipe = Recipe::parse(src);
+    assert_eq!(
+        recipe.steps(),
+        ["Put the bread\n   in the toaster", "Wait", "Butter it"]
+    );
+    let src = "# Toast\n\n## Ingredients\n\n- 1 slice of bread\n\n## Instructions\n\nToast the bread.\n\nButter it,\nthen eat it.\n";
+    let recipe = Recipe::parse(src);
+    assert_eq!(
+        recipe.steps(),
+        ["Toast the bread.", "Butter it,\nthen eat it."]
+    );
+}
+
+#[test]
+fn convert_to() {
+    let cup = Volume::from_cups(1.0);
+    assert_eq!(cup.convert_to("tbsp"), Some(16.0));
+    assert_eq!(cup.convert_to("teaspoons"), Some(48.0));
+    assert_eq!(cup.convert_to("pints"), Some(0.5));
+    assert_eq!(cup.convert_to("hogsheads"), None);
+}
+
+#[test]
+fn invalid_scale_factors() {
+    let recipe = Recipe::parse(include_str!("pizza.md"));
+    let nothing = recipe.scale(0.0);
+    assert_eq!(recipe.scale(-2.0), nothing);
+    assert_eq!(recipe.scale(f32::NAN), nothing);
+    assert_eq!(Volume::from_cups(1.0).scale(-1.0), Volume::from_cups(0.0));
+    assert_eq!(Quantity::Simple(2.0).scale(f32::NAN), Quantity::Simple(0.0));
+}
+
+#[test]
+fn pluralize() {
+    let src = "# Breakfast\n\n## Ingredients\n\n- 1 egg\n- 2 slices of toast\n- 1 loaf of bread\n- 1 cup milk\n";
+    let recipe = Recipe::parse(src);
+    let names = |recipe: &Recipe| -> Vec<String> {
+        recipe
+            .ingredients
+            .iter()
+            .map(|i| i.pluralize().to_string())
+            .collect()
+    };
+    assert_eq!(
+        names(&recipe.scale(2.0)),
+        [
+            "- 2 eggs\n",
+            "- 4 slices of toast\n",
+            "- 2 loaves of bread\n",
+            "- 2 cups milk\n"
+        ]
+    );
+    assert_eq!(names(&recipe.scale(0.5))[1], "- 1 slice of toast\n");
+    let geese = Pluralizer::new().irregular("goose", "geese");
+    let goose = Recipe::parse("# A\n\n## Ingredients\n\n- 1 goose\n");
+    let goose = goose.scale(3.0);
+    assert_eq!(geese.apply(&goose.ingredients[0]).name, "geese");
+}
+
+#[test]
+fn written_amounts() {
+    let src = "# Cake\n\n## Ingredients\n\n- half cup sugar\n- half a cup of milk\n- one egg\n- quarter pound butter\n- half and half\n";
+    let recipe = Recipe::parse(src);
+    let quantities: Vec<&Quantity> = recipe.ingredients.iter().map(|i| &i.quantity).collect();
+    assert_eq!(
+        &quantities[..3],
+        [
+            &Quantity::Volume(Volume::from_cups(0.5)),
+            &Quantity::Volume(Volume::from_cups(0.5)),
+            &Quantity::Simple(1.0),
+        ]
+    );
+    assert!(matches!(quantities[3], Quantity::Weight(_)));
+    assert_eq!(quantities[4], &Quantity::None);
+    assert_eq!(recipe.ingredients[1].name, "of milk");
+    assert_eq!(recipe.ingredients[2].name, "egg");
+}
+
+#[test]
+fn merge() {
+    let recipe = Recipe::parse(include_str!("pizza.md"));
+    let merged = Recipe::merge(&[recipe.clone(), recipe.clone()]);
+    assert_eq!(merged.ingredient_count(), 2 * recipe.ingredient_count());
+    assert_eq!(merged.preface, recipe.preface);
+    assert_eq!(merged.steps().len(), 2 * recipe.steps().len());
+}
+
+#[test]
+fn fractional_scaling() {
+    let recipe = Recipe::parse(include_str!("pizza.md")).scale(0.3);
+    let output = recipe.to_string();
+    assert!(
+        !output
+            .split_whitespace()
+            .any(|word| word.contains('.') && word.split('.').nth(1).is_some_and(|d| d.len() > 2)),
+        "{output}"
+    );
+    for factor in [0.1, 0.3, 0.7, 1.3, 2.9] {
+        let volume = Volume::from_teaspoons(1.0).scale(factor).to_string();
+        assert!(!volume.contains('.'), "{volume}");
+    }
+}
+
+#[test]
+fn ingredient_try_parse() {
+    let ingredient = Ingredient::try_parse("- 2 cups of flour").unwrap();
+    assert_eq!(
+        ingredient.quantity,
+        Quantity::Volume(Volume::from_cups(2.0))
+    );
+    assert_eq!(ingredient.name, "of flour");
+    assert_eq!(ingredient.to_string(), "- 2 cups of flour");
+    assert_eq!(
+        Ingredient::try_parse("2 cups of flour"),
+        Err(ParseError::MissingBullet {
+            line: "2 cups of flour".to_string(),
+            line_number: 1,
+        })
+    );
+}
+
+#[test]
+fn normalized() {
+    let src = "# Messy\n\n## Ingredients\n\n\
+        * 1/2 cup   of  flour  \n\
+        \t+ 3  eggs (beaten)\n\
+        -   salt to taste\n\
+        \n## Instructions\n\nMix.\n\n\n";
+    let recipe = Recipe::parse(src);
+    assert_eq!(format!("{recipe}"), src);
+    assert_eq!(
+        recipe.to_normalized_string(),
+        "# Messy\n\n## Ingredients\n\n\
+        - 1/2 cup of flour\n\
+        \x20 - 3 eggs (beaten)\n\
+        - salt, to taste\n\
+        \n## Instructions\n\nMix.\n"
+    );
+    // Nesting is measured from the item above, however far it's indented
+    let src = "# Salad\n\n## Ingredients\n\n\t- veggies\n\t\t- 1 carrot\n\t- 1 cup rice\n";
+    assert_eq!(
+        Recipe::parse(src).to_normalized_string(),
+        "# Salad\n\n## Ingredients\n\n- veggies\n  - 1 carrot\n- 1 cup rice\n"
+    );
+    let pizza = Recipe::parse(include_str!("pizza.md"));
+    assert_eq!(pizza.to_normalized_string(), include_str!("pizza.md"));
+}
+
+#[test]
+fn temperatures() {
+    let recipe = Recipe::parse(
+        "# Bread\n\n## Ingredients\n\n- 1 loaf\n\n## Instructions\n\n\
+        1. Preheat the oven to 450°F.\n\
+        2. Drop to 200 °C after ten minutes.\n\
+        3. Rest for 5° of arc, for some reason.\n",
+    );
+    let temperatures = recipe.temperatures();
+    assert_eq!(
+        temperatures,
+        [
+            Temperature {
+                degrees: 450.0,
+                unit: TemperatureUnit::Fahrenheit
+            },
+            Temperature {
+                degrees: 200.0,
+                unit: TemperatureUnit::Celsius
+            },
+        ]
+    );
+    assert!((temperatures[0].to_celsius() - 232.2).abs() < 0.1);
+    assert!((temperatures[1].to_fahrenheit() - 392.0).abs() < 0.1);
+}
+
+#[test]
+fn parse_options() {
+    let src = include_str!("pizza.md").replace("## Ingredients", "## Ingredient List");
+    assert_eq!(Recipe::parse(&src).ingredient_count(), 0);
+    let options = ParseOptions::new().headers(["## Ingredients", "## Ingredient List"]);
+    let recipe = Recipe::parse_with_options(&src, &options).unwrap();
+    assert_eq!(recipe.ingredient_count(), 10);
+    assert_eq!(recipe.to_string(), src);
+}
+
+#[test]
+fn crlf() {
+    let src = include_str!("pizza.md").replace('\n', "\r\n");
+    let recipe = Recipe::parse(&src);
+    assert_eq!(recipe.ingredient_count(), 10);
+    assert!(recipe.ingredient_names().all(|name| !name.contains('\r')));
+    let steps = Recipe::parse(include_str!("pizza.md")).steps().len();
+    assert_eq!(recipe.steps().len(), steps);
+    assert_eq!(recipe.to_string(), src);
+
+    let src = include_str!("calzone.md").replace('\n', "\r\n");
+    let recipe = Recipe::parse(&src);
+    assert_eq!(recipe.sections.len(), 3);
+    assert_eq!(recipe.sections[0].title, "For the Dough");
+    assert_eq!(recipe.to_string(), src);
+}
+
+#[test]
+fn fixed_quantities() {
+    let mut recipe = Recipe::parse(include_str!("pizza.md"));
+    recipe.ingredients[0].quantity = Quantity::Fixed(1.0);
+    assert!(!recipe.ingredients[0].quantity.is_scalable());
+    assert!(recipe.ingredients[3].quantity.is_scalable());
+    let scaled = recipe.scale(2.0);
+    assert_eq!(scaled.ingredients[0].quantity, Quantity::Fixed(1.0));
+    assert_eq!(scaled.ingredients[0].to_string(), "- 1 pizza base\n");
+    assert_eq!(recipe.quantity_summary().fixed, 1);
+}
+
+#[test]
+fn round_to_nearest() {
+    let quarter_cup = Volume::from_cups(0.25);
+    let rounded = Volume::from_cups(0.9).round_to_nearest(&quarter_cup);
+    assert_eq!(rounded, Volume::from_cups(1.0));
+    assert_eq!(rounded.to_string(), "1 cup");
+    let rounded = Volume::from_cups(0.3).round_to_nearest(&quarter_cup);
+    assert_eq!(rounded.to_string(), "1/4 cup");
+    let tablespoon = Volume::from_teaspoons(3.0);
+    let rounded = Volume::from_teaspoons(7.0).round_to_nearest(&tablespoon);
+    assert_eq!(rounded.to_string(), "2 tbsps");
+    let volume = Volume::from_cups(0.9);
+    assert_eq!(volume.round_to_nearest(&Volume::from_cups(0.0)), volume);
+}
+
+#[test]
+fn unit_systems() {
+    let pizza_src = include_str!("pizza.md");
+    let recipe = Recipe::parse(pizza_src);
+    assert_eq!(
+        recipe.display_in(UnitSystem::Imperial).to_string(),
+        pizza_src
+    );
+    let metric = recipe.display_in(UnitSystem::Metric).to_string();
+    assert!(metric.contains("\n  - 118 ml of mushrooms\n"), "{metric}");
+    assert!(metric.contains("\n- 7 ml of mixed herbs\n"), "{metric}");
+    assert!(metric.contains("\n- 1 pizza base\n"), "{metric}");
+    assert!(!metric.contains("cup"), "{metric}");
+}
+
+#[test]
+fn front_matter() {
+    let src = format!(
+        "---\nauthor: Madeline\ntags: [pizza, vegan]\nprep time: 20 min\n---\n\n{}",
+        include_str!("pizza.md")
+    );
+    let recipe = Recipe::parse(&src);
+    let front_matter = recipe.front_matter.as_ref().unwrap();
+    assert_eq!(front_matter.len(), 3);
+    assert_eq!(front_matter["author"], "Madeline");
+    assert_eq!(front_matter["tags"], "[pizza, vegan]");
+    assert_eq!(front_matter["prep time"], "20 min");
+    assert_eq!(recipe.title.as_deref(), Some("A fake recipe"));
+    assert_eq!(recipe.ingredient_count(), 10);
+    assert_eq!(recipe.to_string(), src);
+    let pizza = Recipe::parse(include_str!("pizza.md"));
+    assert_eq!(pizza.front_matter, None);
+}
+
+#[test]
+fn validate() {
+    assert_eq!(Recipe::parse(include_str!("pizza.md")).validate(), []);
+    let recipe = Recipe::parse(
+        "# Lints\n\n## Ingredients\n\n\
+        - 2 cups of flour\n\
+        - cup of sugar\n\
+        - 20 gallons of water\n\
+        - 1 tsp of salt\n\
+        - 2 Cups of Flour\n",
+    );
+    let lints = recipe.validate();
+    assert_eq!(
+        lints,
+        [
+            Lint {
+                index: 1,
+                kind: LintKind::MissingQuantity
+            },
+            Lint {
+                index: 2,
+                kind: LintKind::ImplausibleVolume
+            },
+            Lint {
+                index: 4,
+                kind: LintKind::DuplicateIngredient { first: 0 }
+            },
+        ]
+    );
+    assert_eq!(
+        lints[2].to_string(),
+        "ingredient 4 is a duplicate of ingredient 0"
+    );
+}
+
+#[test]
+fn mixed_numbers() {
+    assert_eq!(parse_amount("1 1/2"), Ok(1.5));
+    let recipe = Recipe::parse(
+        "# Mixed\n\n## Ingredients\n\n\
+        - 1 1/2 cups sugar\n\
+        - 2 3/4 lb of potatoes\n\
+        - 3 1/2 eggs\n\
+        - 1 1/2\n",
+    );
+    let quantities: Vec<_> = recipe.all_ingredients().map(|i| &i.quantity).collect();
+    assert_eq!(quantities[0], &Quantity::Volume(Volume::from_cups(1.5)));
+    assert_eq!(recipe.ingredients[0].name, "sugar");
+    let Quantity::Weight(weight) = quantities[1] else {
+        panic!("expected a weight, found {:?}", quantities[1]);
+    };
+    assert!((weight.grams() - 2.75 * 453.592).abs() < 0.1);
+    assert_eq!(quantities[2], &Quantity::Simple(3.5));
+    assert_eq!(recipe.ingredients[2].name, "eggs");
+    assert_eq!(quantities[3], &Quantity::Simple(1.0));
+    assert_eq!(recipe.ingredients[3].name, "1/2");
+}
+
+#[test]
+fn densities() {
+    let table = DensityTable::default();
+    let water = table.get("of water").unwrap();
+    let grams = Volume::from_cups(1.0).to_weight(water).grams();
+    assert!((grams - 236.6).abs() < 0.5, "{grams}");
+    let flour = table.get("all-purpose flour").unwrap();
+    assert!((Volume::from_cups(1.0).to_weight(flour).grams() - 120.0).abs() < 0.01);
+    let brown_sugar = table.get("of Brown Sugar").unwrap();
+    assert!((Volume::from_cups(1.0).to_weight(brown_sugar).grams() - 220.0).abs() < 0.01);
+    assert_eq!(table.get("cauliflower"), None);
+    let recipe = Recipe::parse(include_str!("bread.md"));
+    let Quantity::Weight(weight) = &recipe.ingredients[0].quantity else {
+        panic!("expected a weight");
+    };
+    let volume = weight.to_volume(table.get("flour").unwrap());
+    assert_eq!(volume.to_weight(table.get("flour").unwrap()), *weight);
+    let table = table.density("cauliflower", Density::from_grams_per_cup(107.0));
+    let cauliflower = table.get("cauliflower").unwrap();
+    assert!((Volume::from_cups(1.0).to_weight(cauliflower).grams() - 107.0).abs() < 0.01);
+}
+
+#[test]
+fn map_ingredients() {
+    let recipe = Recipe::parse(include_str!("calzone.md"));
+    let count = recipe.ingredient_count();
+    let mapped = recipe.map_ingredients(|mut ingredient| {
+        ingredient.name = ingredient.name.to_uppercase().into();
+        ingredient
+    });
+    assert_eq!(mapped.ingredient_count(), count);
+    assert!(
+        mapped
+            .ingredient_names()
+            .all(|name| name == name.to_uppercase())
+    );
+    assert_eq!(mapped.sections[2].ingredients[0].name, "OF OLIVES");
+}
+
+#[test]
+fn ingredient_table() {
+    let src = "# Table\n\n## Ingredients\n\n\
+        | Amount | Ingredient |\n\
+        |--------|------------|\n\
+        | 2 cups | flour |\n\
+        | 3      | eggs (large) |\n\
+        |        | salt, to taste |\n\
+        \n## Instructions\n\nMix.\n";
+    let recipe = Recipe::parse(src);
+    assert_eq!(recipe.ingredient_count(), 3);
+    let ingredients = &recipe.ingredients;
+    assert_eq!(
+        ingredients[0].quantity,
+        Quantity::Volume(Volume::from_cups(2.0))
+    );
+    assert_eq!(ingredients[0].name, "flour");
+    assert_eq!(ingredients[1].quantity, Quantity::Simple(3.0));
+    assert_eq!(ingredients[1].name, "eggs");
+    assert_eq!(ingredients[1].note.as_deref(), Some("large"));
+    assert_eq!(ingredients[2].quantity, Quantity::ToTaste { comma: true });
+    assert_eq!(ingredients[2].name, "salt");
+    assert_eq!(recipe.to_string(), src);
+    assert_eq!(
+        recipe.scale(2.0).ingredients[1].to_string(),
+        "| 6      | eggs (large) |\n"
+    );
+}
+
+#[test]
+fn unicode_display() {
+    let cases = [
+        (Volume::from_cups(1.5), "1 + 1/2 cups", "1 + ½ cups"),
+        (Volume::from_cups(0.75), "3/4 cup", "¾ cup"),
+        (Volume::from_cups(2.0 / 3.0), "2/3 cup", "⅔ cup"),
+        (Volume::from_teaspoons(2.25), "2 + 1/4 tsps", "2 + ¼ tsps"),
+        (
+            Volume::from_teaspoons(0.1875),
+            "1/8 + 1/16 tsps",
+            "⅛ + 1/16 tsps",
+        ),
+        (Volume::from_teaspoons(1.0), "1 tsp", "1 tsp"),
+    ];
+    for (volume, ascii, unicode) in cases {
+        assert_eq!(volume.to_string(), ascii);
+        assert_eq!(volume.to_unicode_string(), unicode);
+    }
+}
+
+#[test]
+fn fused_units() {
+    let recipe = Recipe::parse(
+        "# Fused\n\n## Ingredients\n\n\
+        - 2tbsp olive oil\n\
+        - 250g flour\n\
+        - 1.5kg of potatoes\n\
+        - 3x eggs\n",
+    );
+    let ingredients = &recipe.ingredients;
+    assert_eq!(
+        ingredients[0].quantity,
+        Quantity::Volume(Volume::from_teaspoons(6.0))
+    );
+    assert_eq!(ingredients[0].name, "olive oil");
+    assert_eq!(ingredients[1].to_string(), "- 250 g flour\n");
+    assert_eq!(ingredients[2].to_string(), "- 1.5 kg of potatoes\n");
+    assert_eq!(ingredients[3].quantity, Quantity::None);
+    assert_eq!(ingredients[3].name, "3x eggs");
+}
+
+#[test]
+fn recipe_builder() {
+    let recipe = RecipeBuilder::new()
+        .preface("# Pancakes\n\nServes 4\n")
+        .add_ingredient("of flour", Quantity::volume(2.0, "cups").unwrap())
+        .add_ingredient("of butter", Quantity::weight(50.0, "g").unwrap())
+        .add_ingredient("eggs", Quantity::Simple(2.0))
+        .add_ingredient("salt", Quantity::ToTaste { comma: true })
+        .instructions("1. Mix\n2. Fry")
+        .build();
+    let src = "# Pancakes\n\nServes 4\n\n## Ingredients\n\n\
+        - 2 cups of flour\n\
+        - 50 g of butter\n\
+        - 2 eggs\n\
+        - salt, to taste\n\
+        \n## Instructions\n\n1. Mix\n2. Fry\n";
+    assert_eq!(recipe.to_string(), src);
+    assert_eq!(recipe, Recipe::parse(src));
+    assert_eq!(recipe.servings(), Some(4));
+    assert_eq!(recipe.steps(), ["Mix", "Fry"]);
+    assert_eq!(Quantity::volume(1.0, "handfuls"), None);
+}
+
+#[test]
+fn scale_whole() {
+    let recipe = Recipe::parse(
+        "# Omelette\n\n## Ingredients\n\n\
+        - 3 eggs\n\
+        - 3 mushrooms\n\
+        - 3 large egg yolks\n\
+        - 1/2 cup of milk\n\
+        - 3 egg whites\n\
+        - 3 slices of egg bread\n",
+    );
+    assert_eq!(recipe.scale(0.5).ingredients[0].to_string(), "- 1.5 eggs\n");
+    let options = ScaleOptions::new().whole("mushroom");
+    let halved = recipe.scale_with_options(0.5, &options);
+    assert_eq!(halved.ingredients[0].to_string(), "- 2 eggs\n");
+    assert_eq!(halved.ingredients[1].to_string(), "- 2 mushrooms\n");
+    assert_eq!(halved.ingredients[2].to_string(), "- 1.5 large egg yolks\n");
+    assert_eq!(halved.ingredients[3].to_string(), "- 1/4 cup of milk\n");
+    assert_eq!(halved.ingredients[4].to_string(), "- 1.5 egg whites\n");
+    assert_eq!(
+        halved.ingredients[5].to_string(),
+        "- 1.5 slices of egg bread\n"
+    );
+    let options = ScaleOptions::new().whole("yolk").whole("slice");
+    let halved = recipe.scale_with_options(0.5, &options);
+    assert_eq!(halved.ingredients[2].to_string(), "- 2 large egg yolks\n");
+    assert_eq!(
+        halved.ingredients[5].to_string(),
+        "- 2 slices of egg bread\n"
+    );
+    let halved = recipe.scale_with_options(0.5, &ScaleOptions::default());
+    assert_eq!(halved.ingredients[1].to_string(), "- 1.5 mushrooms\n");
+}
+
+#[test]
+fn quantity_accessors() {
+    let recipe = Recipe::parse(include_str!("pizza.md"));
+    let volumes: Vec<_> = recipe
+        .all_ingredients()
+        .filter_map(|i| i.as_volume())
+        .collect();
+    assert_eq!(volumes.len(), 8);
+    assert_eq!(*volumes[0], Volume::from_cups(0.5));
+    let simples: Vec<_> = recipe
+        .all_ingredients()
+        .filter_map(|i| i.as_simple())
+        .collect();
+    assert_eq!(simples, [1.0]);
+    assert_eq!(recipe.ingredients[0].as_volume(), None);
+    assert_eq!(recipe.ingredients[1].as_simple(), None);
+}
+
+#[test]
+fn optional_ingredients() {
+    let src = "# Salad\n\n## Ingredients\n\n\
+        - 1 head of lettuce\n\
+        - 1/4 cup of walnuts (optional)\n\
+        - Optional: 2 tbsps of parmesan\n\
+        - croutons (toasted) (Optional)\n\
+        - pepper, to taste (optional)\n";
+    let recipe = Recipe::parse(src);
+    let optional: Vec<_> = recipe.all_ingredients().map(|i| i.optional).collect();
+    assert_eq!(optional, [false, true, true, true, true]);
+    let ingredients = &recipe.ingredients;
+    assert_eq!(ingredients[1].name, "of walnuts");
+    assert_eq!(ingredients[1].note, None);
+    assert_eq!(
+        ingredients[2].quantity,
+        Quantity::Volume(Volume::from_teaspoons(6.0))
+    );
+    assert_eq!(ingredients[2].name, "of parmesan");
+    assert_eq!(ingredients[3].name, "croutons");
+    assert_eq!(ingredients[3].note.as_deref(), Some("toasted"));
+    assert_eq!(ingredients[4].quantity, Quantity::ToTaste { comma: true });
+    assert_eq!(recipe.to_string(), src);
+
+    let mut lettuce = ingredients[0].clone();
+    lettuce.optional = true;
+    assert_eq!(lettuce.to_string(), "- 1 head of lettuce (optional)\n");
+    let mut parmesan = ingredients[2].clone();
+    parmesan.optional = false;
+    assert_eq!(parmesan.to_string(), "- 2 tbsps of parmesan\n");
+}
+
+#[test]
+fn volume_ordering() {
+    let mut volumes = vec![
+        Volume::from_cups(1.0),
+        Volume::from_teaspoons(1.0),
+        Volume::from_milliliters(500.0),
+        Volume::from_teaspoons(3.0),
+    ];
+    volumes.sort_by(|a, b| a.partial_cmp(b).unwrap());
+    assert_eq!(
+        volumes,
+        [
+            Volume::from_teaspoons(1.0),
+            Volume::from_teaspoons(3.0),
+            Volume::from_cups(1.0),
+            Volume::from_milliliters(500.0),
+        ]
+    );
+    assert!(Volume::from_cups(1.0) >= Volume::from_teaspoons(48.0));
+    assert!(Volume::from_cups(1.0) > Volume::from_cups(0.5));
+    assert_eq!(
+        Volume::from_quarter_teaspoons(1.0).partial_cmp(&Volume::from_quarter_teaspoons(1.001)),
+        Some(std::cmp::Ordering::Equal)
+    );
+}
+
+#[test]
+fn sort_ingredients() {
+    let mut recipe = Recipe::parse(include_str!("pizza.md"));
+    recipe.sort_ingredients(SortKey::Amount);
+    let sorted = "# A fake recipe\n\n\
+        This is a fake recipe used for testing\n\n\
+        ## Ingredients\n\n\
+        - 1/2 cup of vegan cheese\n\
+        - 1/2 cup of pineapple\n\
+        - 1/4 cup of pizza sauce\n\
+        - 2 tsps of another\n\
+        - 1/2 tbsp of mixed herbs\n\
+        - 1/4 tsp of something else\n\
+        - 1 pizza base\n\
+        - veggies\n  \
+          - 1/2 cup of mushrooms\n  \
+          - 1/4 cup of diced capsicum\n\
+        \n## Instructions\n";
+    assert!(recipe.to_string().starts_with(sorted), "{recipe}");
+    assert_eq!(recipe.ingredient_count(), 10);
+
+    recipe.sort_ingredients(SortKey::Name);
+    let names: Vec<_> = recipe.ingredients.iter().map(|i| i.name.as_ref()).collect();
+    assert_eq!(
+        names,
+        [
+            "of another",
+            "of mixed herbs",
+            "of pineapple",
+            "of pizza sauce",
+            "of something else",
+            "of vegan cheese",
+            "pizza base",
+            "veggies",
+            "of diced capsicum",
+            "of mushrooms",
+        ]
+    );
+}
+
+#[test]
+fn tolerant_header() {
+    let pizza_src = include_str!("pizza.md");
+    let variants = [
+        pizza_src.replace("## Ingredients\n\n", "## Ingredients\n"),
+        pizza_src.replace("## Ingredients\n\n", "## Ingredients  \n\n"),
+        pizza_src.replace("## Ingredients\n\n", "## Ingredients\t\n"),
+    ];
+    for src in variants {
+        let recipe = Recipe::parse(&src);
+        assert_eq!(recipe.ingredient_count(), 10);
+        assert_eq!(recipe.to_string(), src);
+    }
+    let src = pizza_src.replace("## Ingredients\n\n", "## Ingredients for two\n\n");
+    assert_eq!(Recipe::parse(&src).ingredient_count(), 0);
+}
+
+#[test]
+fn times() {
+    let recipe = Recipe::parse(
+        "# Roast\n\nPrep: 1 hr 15 min\nCook time: 90 minutes\n\n## Ingredients\n\n- 1 roast\n",
+    );
+    assert_eq!(recipe.prep_time(), Some(Duration::from_secs(75 * 60)));
+    assert_eq!(recipe.cook_time(), Some(Duration::from_secs(90 * 60)));
+    assert_eq!(recipe.total_time(), Some(Duration::from_secs(165 * 60)));
+    let recipe = Recipe::parse("# Toast\n\n- **Cook:** 2h30m\n\nPrepare the bread.\n");
+    assert_eq!(recipe.prep_time(), None);
+    assert_eq!(recipe.cook_time(), Some(Duration::from_secs(150 * 60)));
+    assert_eq!(Recipe::parse(include_str!("pizza.md")).total_time(), None);
+}
+
+#[test]
+fn decimal_comma() {
+    let src = "# Pudding\n\n## Ingredients\n\n\
+        - 0,5 l milk\n\
+        - 1,5kg of apples\n\
+        - 2 eggs, beaten\n";
+    let recipe = Recipe::parse(src);
+    assert_eq!(recipe.ingredients[0].quantity, Quantity::None);
+    let options = ParseOptions::new().decimal_comma(true);
+    let recipe = Recipe::parse_with_options(src, &options).unwrap();
+    assert_eq!(
+        recipe.ingredients[0].quantity,
+        Quantity::Volume(Volume::from_milliliters(500.0))
+    );
+    assert_eq!(recipe.ingredients[0].name, "milk");
+    assert_eq!(recipe.ingredients[1].to_string(), "- 1.5 kg of apples\n");
+    assert_eq!(recipe.ingredients[2].quantity, Quantity::Simple(2.0));
+    assert_eq!(recipe.ingredients[2].name, "eggs, beaten");
+}
+
+// Exercise everything that reads a parsed recipe, none of which should
+// panic whatever the input was
+fn check_parse(src: &str) {
+    let recipe = Recipe::parse(src);
+    let _ = recipe.to_string();
+    let _ = recipe.display_in(UnitSystem::Metric).to_string();
+    let _ = recipe.to_normalized_string();
+    let _ = recipe.scale(0.3).to_string();
+    let _ = recipe.scale(1e30).to_string();
+    let _ = recipe.consolidate().to_string();
+    let _ = recipe.steps();
+    let _ = recipe.divisors();
+    let _ = recipe.validate();
+    let _ = recipe.temperatures();
+    let _ = recipe.total_time();
+    for ingredient in recipe.all_ingredients() {
+        let _ = ingredient.pluralize().to_string();
+        if let Some(volume) = ingredient.as_volume() {
+            let _ = volume.to_unicode_string();
+            let _ = volume.to_string_large_units();
+        }
+    }
+    let mut sorted = recipe.clone();
+    sorted.sort_ingredients(SortKey::Amount);
+    let _ = sorted.to_string();
+}
+
+fn recipe_fragment() -> impl proptest::strategy::Strategy<Value = String> {
+    use proptest::prelude::*;
+    prop_oneof![
+        Just("\n## Ingredients\n\n".to_string()),
+        Just("\n## For the Dough\n\n".to_string()),
+        Just("\n### Extras\n".to_string()),
+        Just("\n## Instructions\n\n".to_string()),
+        Just("---\n".to_string()),
+        Just("\n- ".to_string()),
+        Just("\n  * ".to_string()),
+        Just("\n| ".to_string()),
+        Just(" | ".to_string()),
+        Just("1 1/2 ".to_string()),
+        Just("½ ".to_string()),
+        Just("2-3 ".to_string()),
+        Just("cups ".to_string()),
+        Just("fl oz ".to_string()),
+        Just("g".to_string()),
+        Just("lb ".to_string()),
+        Just("a pinch of ".to_string()),
+        Just("(14 oz) cans ".to_string()),
+        Just("half a ".to_string()),
+        Just("NaN ".to_string()),
+        Just("inf ".to_string()),
+        Just("1e38 ".to_string()),
+        Just("-0 ".to_string()),
+        Just("(optional)".to_string()),
+        Just("Optional: ".to_string()),
+        Just(", to taste".to_string()),
+        Just("450°F".to_string()),
+        Just("Prep: 1h".to_string()),
+        Just("\r\n".to_string()),
+        "\\PC{0,6}",
+    ]
+}
+
+proptest::proptest! {
+    #[test]
+    fn parse_arbitrary(src in "\\PC*") {
+        check_parse(&src);
+    }
+
+    #[test]
+    fn parse_recipe_like(parts in proptest::collection::vec(recipe_fragment(), 0..48)) {
+        check_parse(&parts.concat());
+    }
+}
+
+#[test]
+fn parse_regressions() {
+    // Lines starting with a dash but no bullet used to start an item of
+    // their own, which then failed to parse
+    let rule = "# Pie\n\n## Ingredients\n\n- 1 cup flour\n---\n\n## Instructions\n\nBake.\n";
+    let negative = "# Pie\n\n## Ingredients\n\n- 1 cup flour\n-2 eggs\n";
+    let continued = "# Pie\n\n## Ingredients\n\n- 1 cup flour\n  --- sifted\n- 1 egg\n";
+    for src in [rule, negative, continued] {
+        check_parse(src);
+        let recipe = Recipe::try_parse(src).unwrap();
+        assert_eq!(recipe.to_string(), src);
+    }
+    assert_eq!(Recipe::parse(continued).ingredient_count(), 2);
+    // Ingredients without any bullets are read as preface rather than
+    // panicking
+    let malformed = "# Pie\n\n## Ingredients\n\nflour\n";
+    assert!(Recipe::try_parse(malformed).is_err());
+    assert_eq!(Recipe::parse(malformed).to_string(), malformed);
+    check_parse("\n## Ingredients\n\n\n## Instructions\n\n𝓅450°F");
+    check_parse("Prep: 1e38 h\nCook: 1e38 h\n\n## Ingredients\n\n- 1 cup flour\n");
+    let recipe =
+        Recipe::try_parse("Pie\n\n## Ingredients\n\n- inf cups flour\n- NaN g sugar\n- 1/0 eggs\n")
+            .unwrap();
+    assert!(
+        recipe
+            .all_ingredients()
+            .all(|i| matches!(i.quantity, Quantity::None))
+    );
+    // Negative amounts aren't read as volumes or weights
+    let src = "# Pie\n\n## Ingredients\n\n- -2 cups flour\n- -100g sugar\n";
+    check_parse(src);
+    let recipe = Recipe::try_parse(src).unwrap();
+    assert!(
+        recipe
+            .all_ingredients()
+            .all(|i| i.as_volume().is_none() && !matches!(i.quantity, Quantity::Weight(_)))
+    );
+    assert_eq!(recipe.to_string(), src);
+    assert_eq!(
+        recipe.scale(2.0).to_string(),
+        "# Pie\n\n## Ingredients\n\n- -4 cups flour\n- -100g sugar\n"
+    );
+}
+
+#[test]
+fn simple_fractions() {
+    let src = "Soup\n\n## Ingredients\n\n- 1/2 onion\n- ½ lemon\n- 1 1/2 carrots\n";
+    let recipe = Recipe::try_parse(src).unwrap();
+    assert_eq!(recipe.ingredients[0].as_simple(), Some(0.5));
+    assert_eq!(recipe.to_string(), src);
+    // Scaled amounts no longer match what was written
+    let doubled = recipe.scale(2.0);
+    assert_eq!(doubled.ingredients[0].to_string(), "- 1 onion\n");
+    assert_eq!(doubled.ingredients[2].to_string(), "- 3 carrots\n");
+}
+
+#[test]
+fn hash_ingredients() {
+    use std::collections::HashSet;
+    let a = Ingredient::try_parse("- 1 cup flour").unwrap();
+    // A third of a cup three times over leaves f32 error behind
+    let mut b = Ingredient::try_parse("- 1/3 cup flour").unwrap();
+    b.quantity = Quantity::Volume(b.as_volume().unwrap().scale(3.0));
+    assert_eq!(a, b);
+    let set: HashSet<_> = [a, b].into_iter().collect();
+    assert_eq!(set.len(), 1);
+    let c = Ingredient::try_parse("- 2 cups flour").unwrap();
+    let set: HashSet<_> = set.into_iter().chain([c]).collect();
+    assert_eq!(set.len(), 2);
+}
+
+#[test]
+fn hash_agrees_with_eq() {
+    use std::collections::HashSet;
+    // These sit either side of rounding to a hundredth, so they're close
+    // but not equal, and must not hash alike while being equal
+    let low = Volume::from_quarter_teaspoons(0.004);
+    let high = Volume::from_quarter_teaspoons(0.006);
+    let set: HashSet<_> = [low.clone(), high.clone()].into_iter().collect();
+    assert_eq!(low == high, set.len() == 1);
+    assert_ne!(low, high);
+    assert!(low.approx_eq(&high, 0.01));
+    let same: HashSet<_> = [low.clone(), Volume::from_quarter_teaspoons(0.001)]
+        .into_iter()
+        .collect();
+    assert_eq!(same.len(), 1);
+
+    let low = Quantity::Simple(1.004);
+    let high = Quantity::Simple(1.006);
+    let set: HashSet<_> = [low.clone(), high.clone()].into_iter().collect();
+    assert_eq!(low == high, set.len() == 1);
+    assert_eq!(Quantity::Simple(1.001), Quantity::Simple(1.0));
+
+    let low = Quantity::weight(0.004, "g").unwrap();
+    let high = Quantity::weight(0.006, "g").unwrap();
+    let set: HashSet<_> = [low.clone(), high.clone()].into_iter().collect();
+    assert_eq!(low == high, set.len() == 1);
+}
+
+#[test]
+fn diff() {
+    let pizza_src = include_str!("pizza.md");
+    let pizza = Recipe::parse(pizza_src);
+    assert!(pizza.diff(&pizza).is_empty());
+    let edited_src = pizza_src
+        .replace(
+            "- 1/2 cup of pineapple\n",
+            "- 1/2 cup of pineapple\n- 1 tsp of chili flakes\n",
+        )
+        .replace("- 1/4 cup of pizza sauce", "- 1/3 cup of pizza sauce");
+    let edited = Recipe::parse(&edited_src);
+    let diff = pizza.diff(&edited);
+    assert_eq!(diff.added, ["of chili flakes"]);
+    assert!(diff.removed.is_empty());
+    assert_eq!(
+        diff.changed,
+        [IngredientChange {
+            name: "of pizza sauce",
+            before: &Quantity::volume(0.25, "cup").unwrap(),
+            after: &Quantity::volume(1.0 / 3.0, "cup").unwrap(),
+        }]
+    );
+    assert_eq!(edited.diff(&pizza).removed, ["of chili flakes"]);
+}
+
+#[test]
+fn half_tablespoons() {
+    let tablespoon = Volume::parse("1/2", "tbsp").unwrap();
+    let teaspoons = Volume::parse("1 1/2", "tsp").unwrap();
+    assert_eq!(tablespoon.to_string(), "1/2 tbsp");
+    assert_eq!(teaspoons.to_string(), "1/2 tbsp");
+    // However the same amount was arrived at
+    assert_eq!(
+        Volume::from_teaspoons(0.5).scale(3.0).to_string(),
+        "1/2 tbsp"
+    );
+    assert_eq!(
+        Volume::from_teaspoons(0.15).scale(10.0).to_string(),
+        "1/2 tbsp"
+    );
+    assert_eq!(
+        Volume::from_teaspoons(3.0 + 1.5).to_string(),
+        "1 + 1/2 tbsps"
+    );
+    // Amounts near half a tablespoon stay in teaspoons
+    assert_eq!(
+        Volume::from_teaspoons(1.75).to_string(),
+        "1 + 1/2 + 1/4 tsps"
+    );
+    assert_eq!(
+        Volume::from_teaspoons(3.0 + 1.75).to_string(),
+        "1 tbsp + 1 + 1/2 + 1/4 tsps"
+    );
+}
+
+#[test]
+fn zero_volumes() {
+    let ingredient = Ingredient::try_parse("- 2 cups of flour").unwrap();
+    let none = ingredient.scale(0.0);
+    assert!(none.as_volume().unwrap().is_zero());
+    assert_eq!(none.to_string(), "- of flour");
+    assert!(!ingredient.as_volume().unwrap().is_zero());
+    assert!(!Volume::from_teaspoons(0.01).is_zero());
+}
+
+#[test]
+fn fixed_annotation() {
+    let src = "Stew\n\n## Ingredients\n\n\
+        - 1 tsp salt {fixed}\n\
+        - 2 cups of stock\n\
+        - 1 bay leaf (dried)  {fixed}\n";
+    let recipe = Recipe::try_parse(src).unwrap();
+    assert_eq!(recipe.to_string(), src);
+    let salt = &recipe.ingredients[0];
+    assert!(salt.fixed);
+    assert_eq!(salt.name, "salt");
+    let bay = &recipe.ingredients[2];
+    assert_eq!(
+        (bay.name.as_ref(), bay.note.as_deref()),
+        ("bay leaf", Some("dried"))
+    );
+    assert!(!recipe.ingredients[1].fixed);
+    let tripled = recipe.scale(3.0);
+    assert_eq!(tripled.ingredients[0], recipe.ingredients[0]);
+    assert_eq!(tripled.ingredients[1].to_string(), "- 6 cups of stock\n");
+    assert_eq!(
+        tripled.ingredients[2].to_string(),
+        "- 1 bay leaf (dried)  {fixed}\n"
+    );
+}
+
+#[test]
+fn preface_without_metadata() {
+    let src = "---\nauthor: Maddi\n---\n# Pancakes\n\nServes 4\nPrep: 10 min\n\n\
+        Fluffy pancakes for a slow morning.\n\nBest with berries.\n\n## Ingredients\n\n- 2 eggs\n";
+    let recipe = Recipe::parse(src);
+    assert_eq!(
+        recipe.preface_without_metadata(),
+        "Fluffy pancakes for a slow morning.\n\nBest with berries."
+    );
+    assert_eq!(recipe.to_string(), src);
+    let pizza = Recipe::parse(include_str!("pizza.md"));
+    assert_eq!(
+        pizza.preface_without_metadata(),
+        "This is a fake recipe used for testing"
+    );
+}
+
+#[test]
+fn difficulty_and_rating() {
+    let src = "# Soufflé\n\nDifficulty: Hard\nRating: 4/5\n\n## Ingredients\n\n- 3 eggs\n";
+    let recipe = Recipe::parse(src);
+    assert_eq!(recipe.difficulty, Some(Difficulty::Hard));
+    assert_eq!(recipe.rating, Some(0.8));
+    assert_eq!(recipe.to_string(), src);
+    assert_eq!(recipe.preface_without_metadata(), "");
+    let recipe = Recipe::parse("- **Difficulty:** easy\n- Rating: ★★★☆☆\n\n## Ingredients\n\n");
+    assert_eq!(recipe.difficulty, Some(Difficulty::Easy));
+    assert_eq!(recipe.rating, Some(0.6));
+    assert!(Difficulty::Easy < Difficulty::Hard);
+    let recipe = Recipe::parse("Difficulty: unclear\nRating: 7\n\n## Ingredients\n\n");
+    assert_eq!((recipe.difficulty, recipe.rating), (None, None));
+    let pizza = Recipe::parse(include_str!("pizza.md"));
+    assert_eq!((pizza.difficulty, pizza.rating), (None, None));
+}
+
+#[test]
+fn checked_scale() {
+    let cup = Volume::from_teaspoons(48.0);
+    assert_eq!(cup.checked_scale(2.0), Some(cup.scale(2.0)));
+    assert_eq!(cup.checked_scale(f32::INFINITY), None);
+    assert_eq!(cup.checked_scale(1e38), None);
+    assert_eq!(cup.checked_scale(f32::NAN), None);
+    let pizza = Recipe::parse(include_str!("pizza.md"));
+    assert_eq!(pizza.checked_scale(2.0), Some(pizza.scale(2.0)));
+    assert_eq!(pizza.checked_scale(f32::INFINITY), None);
+    assert_eq!(pizza.checked_scale(1e38), None);
+    assert_eq!(pizza.checked_scale(f32::NAN), None);
+}
+
+#[test]
+fn linked_ingredients() {
+    let src = "Pizza\n\n## Ingredients\n\n\
+        - 1 batch [pizza dough](dough.md)\n\
+        - 1/2 cup of [sauce](sauces/tomato.md) (warmed)\n\
+        - 1 cup of cheese\n";
+    let recipe = Recipe::try_parse(src).unwrap();
+    assert_eq!(recipe.to_string(), src);
+    let dough = &recipe.ingredients[0];
+    assert_eq!(dough.as_simple(), Some(1.0));
+    assert_eq!(dough.link(), Some("dough.md"));
+    assert_eq!(dough.plain_name(), "batch pizza dough");
+    let sauce = &recipe.ingredients[1];
+    assert_eq!(sauce.link(), Some("sauces/tomato.md"));
+    assert_eq!(sauce.plain_name(), "of sauce");
+    assert_eq!(sauce.note.as_deref(), Some("warmed"));
+    assert_eq!(recipe.ingredients[2].link(), None);
+    assert_eq!(recipe.ingredients[2].plain_name(), "of cheese");
+}
+
+#[test]
+fn shopping_list() {
+    let pizza = Recipe::parse(include_str!("pizza.md"));
+    let list = pizza.shopping_list();
+    let list: Vec<_> = list
+        .iter()
+        .map(|item| (item.category.as_deref(), item.to_string()))
+        .collect();
+    assert_eq!(
+        list,
+        [
+            (Some("dairy"), "1/2 cup vegan cheese".to_string()),
+            (Some("pantry"), "1/4 cup pizza sauce".to_string()),
+            (Some("produce"), "1/2 cup mushrooms".to_string()),
+            (Some("produce"), "1/4 cup diced capsicum".to_string()),
+            (Some("produce"), "1/2 cup pineapple".to_string()),
+            (Some("spices"), "1/2 tbsp mixed herbs".to_string()),
+            (None, "1 pizza base".to_string()),
+            (None, "1/4 tsp something else".to_string()),
+            (None, "2 tsps another".to_string()),
+        ]
+    );
+    // Quantities of the same ingredient are totalled
+    let doubled = Recipe::merge(&[pizza.clone(), pizza.clone()]);
+    let categories = CategoryTable::new().category("pizza base", "bakery");
+    let list = doubled.shopping_list_with(&categories);
+    assert_eq!(list.len(), 9);
+    assert_eq!(list[0].category.as_deref(), Some("bakery"));
+    assert_eq!(list[0].to_string(), "2 pizza base");
+    assert_eq!(categories.get("Eggs"), Some("dairy"));
+    assert_eq!(categories.get("eggplant"), None);
+}
+
+#[test]
+fn leading_text() {
+    let src = "Salad\n\n## Ingredients\n\n\
+        Note - buy everything fresh:\n\
+        - 1 cup of lettuce\n\
+        - 2 tomatoes\n";
+    let recipe = Recipe::try_parse(src).unwrap();
+    assert_eq!(
+        recipe.ingredient_names().collect::<Vec<_>>(),
+        ["of lettuce", "tomatoes"]
+    );
+    assert_eq!(recipe.ingredients[0].depth(), 0);
+    assert_eq!(recipe.to_string(), src);
+    assert_eq!(
+        recipe.scale(2.0).ingredients[0].to_string(),
+        "Note - buy everything fresh:\n- 2 cups of lettuce\n"
+    );
+    // Prose on its own still isn't an ingredient
+    assert!(Ingredient::try_parse("Note - buy fresh").is_err());
+}
+
+#[test]
+fn iterate_ingredients() {
+    let pizza = Recipe::parse(include_str!("pizza.md"));
+    let mut names = vec![];
+    for ingredient in &pizza {
+        names.push(ingredient.name.as_ref());
+    }
+    assert_eq!(names.len(), 10);
+    assert_eq!(names[..3], ["pizza base", "veggies", "of mushrooms"]);
+    assert!((&pizza).into_iter().eq(pizza.all_ingredients()));
+}
+
+#[test]
+fn unit_periods() {
+    let vanilla = Ingredient::try_parse("- 1 tsp. vanilla").unwrap();
+    assert_eq!(vanilla.as_volume(), Some(&Volume::from_teaspoons(1.0)));
+    assert_eq!(vanilla.name, "vanilla");
+    let flour = Ingredient::try_parse("- 200 g. flour").unwrap();
+    assert_eq!(flour.quantity, Quantity::weight(200.0, "g").unwrap());
+    assert_eq!(flour.name, "flour");
+    let oil = Ingredient::try_parse("- 2 Tbsp. oil").unwrap();
+    assert_eq!(oil.as_volume(), Some(&Volume::from_teaspoons(6.0)));
+    let milk = Ingredient::try_parse("- 4 fl. oz. milk").unwrap();
+    assert_eq!(milk.as_volume(), Some(&Volume::from_teaspoons(24.0)));
+    assert_eq!(milk.name, "milk");
+}
+
+#[test]
+fn map_text() {
+    let pizza_src = include_str!("pizza.md");
+    let pizza = Recipe::parse(pizza_src);
+    let reworded = pizza
+        .clone()
+        .map_instructions(|instructions| instructions.replace("everyting", "everything"));
+    assert_ne!(reworded.instructions, pizza.instructions);
+    assert_eq!(
+        reworded.to_string(),
+        pizza_src.replace("everyting", "everything")
+    );
+    assert_eq!(reworded.ingredients, pizza.ingredients);
+    let renamed = pizza
+        .map_preface(|preface| preface.replace("# A fake recipe", "# A real recipe\n\nServes 2"));
+    assert_eq!(renamed.title.as_deref(), Some("A real recipe"));
+    assert_eq!(renamed.servings(), Some(2));
+}
+
+#[test]
+fn notes_section() {
+    let src = "# Bread\n\nA simple loaf.\n\n## Ingredients\n\n- 3 cups of flour\n- 1 cup of water\n\n\
+        ## Instructions\n\n1. Mix\n2. Bake at 220°C\n\n## Notes\n\nKeeps for 3 days.\nFreezes well.\n";
+    let recipe = Recipe::parse(src);
+    assert_eq!(recipe.to_string(), src);
+    assert_eq!(
+        recipe.instructions,
+        "\n## Instructions\n\n1. Mix\n2. Bake at 220°C\n"
+    );
+    assert_eq!(
+        recipe.notes.as_deref(),
+        Some("\n## Notes\n\nKeeps for 3 days.\nFreezes well.\n")
+    );
+    assert_eq!(recipe.steps(), ["Mix", "Bake at 220°C"]);
+    assert_eq!(recipe.scale(2.0).notes, recipe.notes);
+    assert!(
+        recipe
+            .to_normalized_string()
+            .ends_with("## Notes\n\nKeeps for 3 days.\nFreezes well.\n")
+    );
+    assert_eq!(Recipe::parse(include_str!("pizza.md")).notes, None);
+}
+
+#[test]
+fn packages() {
+    let src = "Chili\n\n## Ingredients\n\n\
+        - 3 (14 oz) cans tomatoes\n\
+        - 2 (400 g) tins of kidney beans\n\
+        - 1 (2 cups) jar of salsa\n\
+        - 2 (fresh) limes\n";
+    let recipe = Recipe::try_parse(src).unwrap();
+    assert_eq!(recipe.to_string(), src);
+    let tomatoes = &recipe.ingredients[0];
+    assert_eq!(tomatoes.name, "tomatoes");
+    assert_eq!(
+        tomatoes.quantity,
+        Quantity::Package {
+            count: 3.0,
+            size: Box::new(Quantity::weight(14.0, "oz").unwrap()),
+            unit: "cans".to_string(),
+        }
+    );
+    assert_eq!(recipe.ingredients[1].name, "of kidney beans");
+    // Not a package, so the parenthetical stays in the name
+    assert_eq!(recipe.ingredients[3].as_simple(), Some(2.0));
+    let doubled = recipe.scale(2.0);
+    assert_eq!(
+        doubled.ingredients[0].to_string(),
+        "- 6 (14 oz) cans tomatoes\n"
+    );
+    assert_eq!(
+        doubled.ingredients[2].to_string(),
+        "- 2 (2 cups) jar of salsa\n"
+    );
+    assert_eq!(recipe.quantity_summary().package, 3);
+}
+
+#[test]
+fn scale_rounded() {
+    let pizza = Recipe::parse(include_str!("pizza.md"));
+    let eighth = Volume::from_teaspoons(0.125).quarter_teaspoons();
+    for factor in [0.37, 1.13, 2.71] {
+        let scaled = pizza.scale_rounded(factor);
+        for volume in scaled.all_ingredients().filter_map(Ingredient::as_volume) {
+            let eighths = volume.quarter_teaspoons() / eighth;
+            assert!((eighths - eighths.round()).abs() < 1e-3, "{volume:?}");
+        }
+        assert!(!scaled.to_string().contains("1/16"));
+    }
+    // 1/4 tsp scaled by 0.37 would be closest to a sixteenth
+    assert!(pizza.scale(0.37).to_string().contains("1/16"));
+    assert_eq!(pizza.scale_rounded(1.0), pizza);
+}
+
+#[test]
+fn unit_labels() {
+    let german = VolumeFormatter::new().labels(
+        UnitLabels::new()
+            .cup("Tasse", "Tassen")
+            .tablespoon("EL", "EL")
+            .teaspoon("TL", "TL")
+            .pinch("eine Prise"),
+    );
+    let volume = Volume::from_teaspoons(2.0 * 48.0 + 3.0 + 1.0);
+    assert_eq!(german.format(&volume), "2 Tassen + 1 EL + 1 TL");
+    assert_eq!(german.format(&Volume::from_teaspoons(12.0)), "1/4 Tasse");
+    assert_eq!(german.format(&Volume::from_teaspoons(0.01)), "eine Prise");
+    // The default labels are unchanged
+    assert_eq!(
+        VolumeFormatter::new().format(&volume),
+        "2 cups + 1 tbsp + 1 tsp"
+    );
+}
+
+#[test]
+fn name_first() {
+    let src = "Cake\n\n## Ingredients\n\n\
+        - Flour: 2 cups\n\
+        - Sugar - 1/2 cup\n\
+        - Eggs: 3\n\
+        - Butter (softened):  100 g\n\
+        - Salt, to taste\n\
+        - 1 tsp vanilla\n";
+    let options = ParseOptions::new().name_first(true);
+    let recipe = Recipe::parse_with_options(src, &options).unwrap();
+    assert_eq!(recipe.to_string(), src);
+    let flour = &recipe.ingredients[0];
+    assert_eq!(flour.name, "Flour");
+    assert_eq!(flour.as_volume(), Some(&Volume::from_teaspoons(96.0)));
+    assert_eq!(recipe.ingredients[1].name, "Sugar");
+    assert_eq!(recipe.ingredients[2].as_simple(), Some(3.0));
+    let butter = &recipe.ingredients[3];
+    assert_eq!(
+        (butter.name.as_ref(), butter.note.as_deref()),
+        ("Butter", Some("softened"))
+    );
+    assert_eq!(butter.quantity, Quantity::weight(100.0, "g").unwrap());
+    assert_eq!(
+        recipe.ingredients[4].quantity,
+        Quantity::ToTaste { comma: true }
+    );
+    assert_eq!(recipe.ingredients[5].name, "vanilla");
+    assert_eq!(
+        recipe.scale(2.0).ingredients[0].to_string(),
+        "- Flour: 4 cups\n"
+    );
+    // Without the option, the amount stays in the name
+    let recipe = Recipe::parse(src);
+    assert_eq!(recipe.ingredients[0].name, "Flour: 2 cups");
+}
+
+#[test]
+fn volume_approx_eq() {
+    let cup = Volume::from_cups(1.0);
+    let teaspoons = Volume::from_teaspoons(48.0);
+    assert!(cup.approx_eq(&teaspoons, 0.01));
+    let round_trip = cup.scale(1.0 / 3.0).scale(3.0);
+    assert!(round_trip.approx_eq(&cup, 0.01));
+    assert!(!cup.approx_eq(&Volume::from_teaspoons(47.0), 1.0));
+    assert!(cup.approx_eq(&Volume::from_teaspoons(47.0), 4.0));
+}
+
+#[test]
+fn total_volume() {
+    let src = "# Dressing\n\n## Ingredients\n\n- 1/2 cup olive oil\n- 2 tbsp vinegar\n- 1 tsp mustard\n- 2 cloves garlic\n- 10 g salt\n- Pepper\n";
+    let recipe = Recipe::parse(src);
+    let expected = Volume::from_cups(0.5) + Volume::from_teaspoons(7.0);
+    assert_eq!(recipe.total_volume(), expected);
+    let empty = Recipe::parse("# Nothing\n\n## Ingredients\n\n- Salt\n");
+    assert!(empty.total_volume().is_zero());
+}
+
+#[test]
+fn bullet_spacing() {
+    let src = "# Toast\n\n## Ingredients\n\n-  2 slices bread\n- 1 tbsp butter\n*   Jam\n";
+    let recipe = Recipe::parse(src);
+    assert_eq!(recipe.to_string(), src);
+    assert_eq!(recipe.ingredients[0].bullet_spacing.as_deref(), Some("  "));
+    assert_eq!(recipe.ingredients[0].as_simple(), Some(2.0));
+    assert_eq!(recipe.ingredients[0].name, "slices bread");
+    assert_eq!(recipe.ingredients[1].bullet_spacing, None);
+    assert_eq!(recipe.ingredients[2].name, "Jam");
+    assert!(
+        recipe
+            .to_normalized_string()
+            .contains("\n- 2 slices bread\n")
+    );
+}
+
+#[test]
+fn missing_ingredients_section() {
+    let recipe = Recipe::parse("# Toast\n\nJust some thoughts on toast.\n");
+    assert!(!recipe.has_ingredients_section);
+    assert!(recipe.is_empty());
+
+    let recipe = Recipe::parse("# Toast\n\n## Ingredients\n\n");
+    assert!(recipe.has_ingredients_section);
+    assert!(recipe.ingredients.is_empty());
+    assert!(recipe.is_empty());
+
+    let recipe = Recipe::parse("# Toast\n\n## Ingredients\n\n## Instructions\n\nToast it.\n");
+    assert!(recipe.has_ingredients_section);
+    assert!(!recipe.is_empty());
+    assert!(!Recipe::parse(include_str!("pizza.md")).is_empty());
+}
+
+#[test]
+fn formatter_without_tablespoons() {
+    let three = Volume::from_teaspoons(3.0);
+    assert_eq!(VolumeFormatter::new().format(&three), "1 tbsp");
+    let teaspoons = VolumeFormatter::new().tablespoons(false);
+    assert_eq!(teaspoons.format(&three), "3 tsps");
+    let volume = Volume::from_cups(0.25) + Volume::from_teaspoons(2.0);
+    assert_eq!(teaspoons.format(&volume), "1/4 cup + 2 tsps");
+    assert_eq!(teaspoons.format(&Volume::from_cups(2.0)), "2 cups");
+}
+
+#[test]
+fn with_quantity_and_name() {
+    let recipe = Recipe::parse(include_str!("calzone.md"));
+    let original = recipe.sections[2].ingredients[0].clone();
+    let doubled = original.clone().with_quantity(original.quantity.scale(2.0));
+    assert_eq!(doubled.indent, original.indent);
+    assert_eq!(doubled.name, original.name);
+    assert_eq!(doubled.trailing, original.trailing);
+    assert_eq!(doubled.quantity, original.quantity.scale(2.0));
+
+    let renamed = original.clone().with_name("black olives");
+    assert_eq!(renamed.name, "black olives");
+    assert_eq!(renamed.quantity, original.quantity);
+    assert_eq!(renamed.indent, original.indent);
+
+    let mapped =
+        recipe.map_ingredients(|ingredient| ingredient.with_quantity(Quantity::Simple(1.0)));
+    assert!(mapped.all_ingredients().all(|i| i.as_simple() == Some(1.0)));
+}
+
+#[test]
+fn preparation() {
+    let src = "# Salsa\n\n## Ingredients\n\n\
+        - 2 apples, diced\n\
+        - 1 onion, finely chopped (red)\n\
+        - pepper, freshly ground, to taste\n\
+        - 1 cup stock\n";
+    let recipe = Recipe::parse(src);
+    assert_eq!(recipe.ingredients[0].name, "apples, diced");
+    assert_eq!(recipe.ingredients[0].preparation, None);
+    let options = ParseOptions::new().preparation(true);
+    let recipe = Recipe::parse_with_options(src, &options).unwrap();
+    assert_eq!(recipe.to_string(), src);
+    assert_eq!(recipe.ingredients[0].name, "apples");
+    assert_eq!(recipe.ingredients[0].preparation.as_deref(), Some("diced"));
+    assert_eq!(recipe.ingredients[1].name, "onion");
+    assert_eq!(
+        recipe.ingredients[1].preparation.as_deref(),
+        Some("finely chopped")
+    );
+    assert_eq!(recipe.ingredients[1].note.as_deref(), Some("red"));
+    assert_eq!(recipe.ingredients[2].name, "pepper");
+    assert_eq!(
+        recipe.ingredients[2].preparation.as_deref(),
+        Some("freshly ground")
+    );
+    assert_eq!(recipe.ingredients[3].preparation, None);
+    let doubled = recipe.scale(2.0);
+    assert_eq!(doubled.ingredients[0].to_string(), "- 4 apples, diced\n");
+    assert_eq!(
+        doubled.ingredients[1].to_string(),
+        "- 2 onion, finely chopped (red)\n"
+    );
+}
+
+// Each fixture is checked at each factor against 'golden/{name}.x{factor}.md',
+// which can be rewritten after an intended change by running the tests with
+// UPDATE_GOLDEN set
+const GOLDEN_FIXTURES: [(&str, &str); 5] = [
+    ("pizza", include_str!("pizza.md")),
+    ("bread", include_str!("bread.md")),
+    ("calzone", include_str!("calzone.md")),
+    ("stars", include_str!("stars.md")),
+    ("measures", include_str!("measures.md")),
+];
+const GOLDEN_FACTORS: [f32; 3] = [0.5, 2.0, 3.0];
+
+#[test]
+fn golden() {
+    let dir = std::path::Path::new(env!("CARGO_MANIFEST_DIR")).join("src/tests/golden");
+    let update = std::env::var_os("UPDATE_GOLDEN").is_some();
+    let mut mismatches = vec![];
+    for (name, src) in GOLDEN_FIXTURES {
+        let recipe = Recipe::parse(src);
+        for factor in GOLDEN_FACTORS {
+            let path = dir.join(format!("{name}.x{factor}.md"));
+            let rendered = recipe.scale(factor).to_string();
+            if update {
+                std::fs::write(&path, &rendered).unwrap();
+                continue;
+            }
+            let expected = std::fs::read_to_string(&path).unwrap_or_default();
+            if rendered != expected {
+                mismatches.push(format!("{}:\n{rendered}", path.display()));
+            }
+        }
+    }
+    assert!(
+        mismatches.is_empty(),
+        "output differs from the golden files, rerun with UPDATE_GOLDEN \
+         set if that's intended:\n\n{}",
+        mismatches.join("\n")
+    );
+}
+
+#[test]
+fn checklist() {
+    let src = "# Toast\n\n## Ingredients\n\n- 1 slice of bread\n\n## Instructions\n\n- [x] Put the bread\n  in the toaster\n- [ ] Wait\n* [X] Butter it\n- [] Not a task\n";
+    let recipe = Recipe::parse(src);
+    assert_eq!(
+        recipe.checklist(),
+        [
+            TaskStep {
+                text: "Put the bread\n  in the toaster",
+                checked: true
+            },
+            TaskStep {
+                text: "Wait",
+                checked: false
+            },
+            TaskStep {
+                text: "Butter it",
+                checked: true
+            },
+        ]
+    );
+    assert_eq!(
+        recipe.steps(),
+        ["Put the bread\n  in the toaster", "Wait", "Butter it"]
+    );
+    let nested = Recipe::parse(
+        "# Toast\n\n## Ingredients\n\n- Bread\n\n## Instructions\n\n\
+        - [ ] Spread on\n  - butter\n  - jam\n- [ ] Eat it\n",
+    );
+    assert_eq!(nested.steps(), ["Spread on\n  - butter\n  - jam", "Eat it"]);
+    let numbered =
+        Recipe::parse("# Toast\n\n## Ingredients\n\n- Bread\n\n## Instructions\n\n1. Toast it\n");
+    assert_eq!(numbered.steps(), ["Toast it"]);
+    assert!(numbered.checklist().is_empty());
+}
+
+#[test]
+fn display_in_unit() {
+    let volume = Volume::from_teaspoons(9.6);
+    assert_eq!(volume.display_in_unit("tbsp").as_deref(), Some("3.2 tbsp"));
+    assert_eq!(
+        Volume::from_cups(1.0).display_in_unit("tbsp").as_deref(),
+        Some("16 tbsp")
+    );
+    assert_eq!(
+        Volume::from_milliliters(47.32)
+            .display_in_unit("ml")
+            .as_deref(),
+        Some("47.3 ml")
+    );
+    assert_eq!(volume.display_in_unit("grams"), None);
+}
+
+#[test]
+fn negative_volumes() {
+    let nothing = Volume::from_cups(0.0);
+    assert_eq!(Volume::from_cups(-1.0), nothing);
+    assert_eq!(Volume::from_teaspoons(-2.5), nothing);
+    assert_eq!(Volume::from_milliliters(-30.0), nothing);
+    assert_eq!(
+        Volume::from_quarter_teaspoons(-1.0).quarter_teaspoons(),
+        0.0
+    );
+    assert!(Volume::from_cups(-1.0).is_zero());
+    assert_eq!(Volume::from_cups(-1.0).to_string(), nothing.to_string());
+}
@@ -0,0 +1,60 @@
+// SPDX-FileCopyrightText: 2025 Madeline Baggins <madeline@baggins.family>
+//
+// SPDX-License-Identifier: GPL-3.0-only
+
+#![cfg(feature = "std")]
+
+use std::process::{Command, Output};
+
+use maddi_recipe::Recipe;
+
+const PIZZA: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/tests/pizza.md");
+const SOUP: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/src/tests/soup.md");
+
+fn maddi_recipe(args: &[&str]) -> Output {
+    Command::new(env!("CARGO_BIN_EXE_maddi-recipe"))
+        .args(args)
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn prints_unscaled() {
+    let output = maddi_recipe(&[PIZZA]);
+    assert!(output.status.success());
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout, include_str!("../src/tests/pizza.md"));
+}
+
+#[test]
+fn scales() {
+    let output = maddi_recipe(&[PIZZA, "--scale", "2"]);
+    assert!(output.status.success());
+    let pizza = Recipe::parse(include_str!("../src/tests/pizza.md"));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout, pizza.scale(2.0).to_string());
+}
+
+#[test]
+fn scales_to_servings() {
+    let output = maddi_recipe(&[SOUP, "--servings", "6"]);
+    assert!(output.status.success());
+    let soup = Recipe::parse(include_str!("../src/tests/soup.md"));
+    let stdout = String::from_utf8(output.stdout).unwrap();
+    assert_eq!(stdout, soup.scale_to(6).unwrap().to_string());
+    assert!(stdout.contains("- 3 cups of broth"));
+}
+
+#[test]
+fn reports_errors() {
+    let output = maddi_recipe(&[PIZZA, "--scale", "lots"]);
+    assert!(!output.status.success());
+    let stderr = String::from_utf8(output.stderr).unwrap();
+    assert!(stderr.contains("invalid scale factor 'lots'"));
+    // The pizza doesn't say how many it serves
+    let output = maddi_recipe(&[PIZZA, "--servings", "4"]);
+    assert!(!output.status.success());
+    let output = maddi_recipe(&["no-such-recipe.md"]);
+    assert!(!output.status.success());
+    assert!(output.stdout.is_empty());
+}